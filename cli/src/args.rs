@@ -1,22 +1,37 @@
 use std::path::PathBuf;
 
 use clap::{Args, Subcommand};
-use lib::TaskID;
+use lib::{TaskID, DEFAULT_HACK_ATTEMPTS};
 
 #[derive(Debug, Args)]
 #[command(author, version, about, long_about = None, arg_required_else_help(true))]
 pub struct Cli {
-    #[arg(long, default_value = "cdf.toml")]
-    /// Path to config
-    config: PathBuf,
+    #[arg(long, env = "CDF_CONFIG")]
+    /// Path to config. By default, cdf.toml is searched in current and parent directories
+    config: Option<PathBuf>,
+
+    #[arg(long, short, global = true)]
+    /// Print compiler warnings of successful builds
+    verbose: bool,
+
+    #[arg(long, global = true)]
+    /// Run solutions in sandbox: without network, with read-only file system and private
+    /// copy of working directory. Linux only
+    sandbox: bool,
 
     #[command(subcommand)]
     pub(crate) command: Option<Commands>,
 }
 
 impl Cli {
-    pub(crate) fn config(&self) -> &PathBuf {
-        &self.config
+    pub(crate) fn config(&self) -> Option<&PathBuf> {
+        self.config.as_ref()
+    }
+    pub(crate) fn verbose(&self) -> bool {
+        self.verbose
+    }
+    pub(crate) fn sandbox(&self) -> bool {
+        self.sandbox
     }
 }
 
@@ -24,15 +39,131 @@ impl Cli {
 pub(crate) enum Commands {
     /// Add a new task
     Add,
+    /// Create solution source of task from template
+    New {
+        /// Task id
+        id: TaskID,
+        /// Overwrite existing source
+        #[arg(long, short)]
+        force: bool,
+    },
     /// Run test for specific task
     Test {
         /// Test id
         id: TaskID,
+        /// Build profile from build settings, e.g. `debug`
+        #[arg(long, short)]
+        profile: Option<String>,
+    },
+    /// Build with `debug` profile and run debugger with input of test
+    Debug {
+        /// Task id
+        id: TaskID,
+        /// Test number, starting from 1
+        n: usize,
+    },
+    /// Run each test several times and compare timings with baseline
+    Bench {
+        /// Task id
+        id: TaskID,
+        /// Number of runs of each test
+        #[arg(long, short, default_value_t = 5)]
+        repeat: usize,
+        /// Do not save timings as new baseline
+        #[arg(long)]
+        no_save: bool,
+    },
+    /// Show results of previous test runs of task
+    History {
+        /// Task id
+        id: TaskID,
+        /// Number of latest runs to show
+        #[arg(long, short = 'n', default_value_t = 10)]
+        limit: usize,
     },
+    /// Show tests of task, including generated ones
+    Show {
+        /// Task id
+        id: TaskID,
+    },
+    /// Run program with custom input, without saving a test
+    Run {
+        /// Task id
+        id: TaskID,
+        /// Read input from file instead of stdin
+        #[arg(long, short)]
+        input: Option<PathBuf>,
+        /// Save input and actual output as a new test
+        #[arg(long)]
+        save: bool,
+    },
+    /// Save actual output of program as expected output of tests
+    Accept {
+        /// Task id
+        id: TaskID,
+        /// Test number, accept all tests if not specified
+        n: Option<usize>,
+    },
+    /// Bundle solution with local headers into single file, and check it passes tests
+    Bundle {
+        /// Task id
+        id: TaskID,
+        /// Remove `#ifdef LOCAL` blocks
+        #[arg(long)]
+        strip_local: bool,
+        /// Do not build and test bundled source
+        #[arg(long)]
+        no_verify: bool,
+    },
+    /// Bundle solution and submit it to server from [settings.submit]
+    Submit {
+        /// Task id
+        id: TaskID,
+        /// Do not build and test bundled source before submitting
+        #[arg(long)]
+        no_verify: bool,
+        /// Do not wait for verdict
+        #[arg(long)]
+        no_wait: bool,
+    },
+    /// Check inputs of tests with validator of task
+    Validate {
+        /// Task id
+        id: TaskID,
+    },
+    /// Search for input, on which someone else's solution gives wrong output
+    Hack {
+        /// Task id
+        id: TaskID,
+        /// Source of solution, built with build settings of task
+        #[arg(long, short)]
+        source: PathBuf,
+        /// Number of inputs from generator of task, tried after tests
+        #[arg(long, short = 'n', default_value_t = DEFAULT_HACK_ATTEMPTS)]
+        attempts: u64,
+        /// Do not run foreign solution in sandbox by default on Linux, `--sandbox` and
+        /// settings still apply
+        #[arg(long)]
+        no_sandbox: bool,
+    },
+    /// Manage config
+    Config {
+        #[command(subcommand)]
+        command: ConfigCommands,
+    },
+    /// Manage contests
+    Contest {
+        #[command(subcommand)]
+        command: ContestCommands,
+    },
+    /// Check config for errors
+    Check,
+    /// Print JSON Schema of config, for completion and validation in editors
+    Schema,
     /// Format config file
     #[clap(name = "fmt")]
     Format,
-    /// Initialize default config in current directory
+    /// Initialize default config and its JSON Schema in current directory
     #[clap(name = "init")]
     Init {
         /// Use sample config instead of minimal
@@ -40,3 +171,46 @@ pub(crate) enum Commands {
         sample: bool,
     },
 }
+
+impl Commands {
+    /// Command builds or runs solution, so it can be cancelled with Ctrl+C
+    pub(crate) fn runs_programs(&self) -> bool {
+        matches!(
+            self,
+            Self::Test { .. }
+                | Self::Bench { .. }
+                | Self::Run { .. }
+                | Self::Accept { .. }
+                | Self::Bundle { .. }
+                | Self::Submit { .. }
+                | Self::Hack { .. }
+                | Self::Validate { .. }
+        )
+    }
+}
+
+#[derive(Debug, Subcommand)]
+pub(crate) enum ConfigCommands {
+    /// Show effective config merged with user config, and where each value came from
+    Show,
+}
+
+#[derive(Debug, Subcommand)]
+pub(crate) enum ContestCommands {
+    /// Create tasks of contest with their sources and samples
+    Init {
+        /// Problem ids, e.g. "a-h" or "a,b,c1,c2"
+        #[arg(long, short, required_unless_present = "from")]
+        problems: Option<String>,
+        /// Saved Codeforces contest page (dashboard or complete problemset) to import problems
+        /// and samples from
+        #[arg(long)]
+        from: Option<PathBuf>,
+        /// Contest id, taken from page if not set
+        #[arg(long)]
+        id: Option<String>,
+        /// Contest name, taken from page if not set
+        #[arg(long)]
+        name: Option<String>,
+    },
+}
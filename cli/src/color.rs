@@ -0,0 +1,30 @@
+use std::{
+    env::var_os,
+    io::{stderr, stdout, IsTerminal},
+};
+
+const RED: &str = "31";
+const YELLOW: &str = "33";
+
+/// Colors are used only in terminal, and can be disabled with `NO_COLOR`
+fn enabled() -> bool {
+    stdout().is_terminal()
+        && stderr().is_terminal()
+        && var_os("NO_COLOR").is_none_or(|v| v.is_empty())
+}
+
+fn paint(text: &str, code: &str) -> String {
+    if enabled() {
+        format!("\x1b[{code}m{text}\x1b[0m")
+    } else {
+        text.into()
+    }
+}
+
+pub(crate) fn red(text: &str) -> String {
+    paint(text, RED)
+}
+
+pub(crate) fn yellow(text: &str) -> String {
+    paint(text, YELLOW)
+}
@@ -1,34 +1,75 @@
-use std::fs::read_to_string;
+use std::{
+    env::current_dir,
+    fs::read_to_string,
+    io::{stdin, stdout, IsTerminal, Write},
+    path::{Path, PathBuf},
+    process::exit,
+    thread::sleep,
+    time::{Duration, Instant},
+};
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 
-use args::Commands;
+use args::{Commands, ConfigCommands, ContestCommands};
 use input::{read_line_with_prompt, read_until_eof_with_prompt};
-use lib::{Config, FailedTest, TaskID, TestResult};
+use lib::{
+    contest_url, parse_problem_ids, BenchResult, BuildDiagnostic, CommandOutput, Config, Contest,
+    ContestPage, Error as LibError, FailedTest, HackResult, Problem, TaskID, TaskLocation,
+    TestChange, TestResult, ValueOrigin, CONFIG_FILE_NAME, DEBUG_PROFILE,
+};
 
 pub use args::Cli;
 
 mod args;
+mod color;
 mod input;
 
 #[cfg(target_family = "unix")]
 const EOF_KEYBOARD: &str = "Ctrl+D";
 #[cfg(target_family = "windows")]
 const EOF_KEYBOARD: &str = "Ctrl+Z";
+/// Characters of input shown by `cdf show`
+const SHOW_INPUT_WIDTH: usize = 60;
+
+const VERDICT_POLL_INTERVAL: Duration = Duration::from_secs(2);
+const VERDICT_TIMEOUT: Duration = Duration::from_secs(300);
+/// Exit code of process interrupted by SIGINT
+const INTERRUPTED_EXIT_CODE: i32 = 130;
 
 pub fn main(cli: &Cli) -> Result<()> {
-    let config_path = cli.config().clone();
+    let config_path = match cli.config() {
+        Some(path) => path.clone(),
+        None if matches!(cli.command, Some(Commands::Init { .. })) => CONFIG_FILE_NAME.into(),
+        None => match Config::find(&current_dir()?) {
+            Some(path) => path,
+            None => {
+                bail!("{CONFIG_FILE_NAME} not found in current directory or any parent directory")
+            }
+        },
+    };
     if let Some(Commands::Init { sample }) = cli.command {
         if sample {
             Config::save_sample_to(&config_path)?;
         } else {
-            Config::default().save_config_to(&config_path)?;
+            Config::minimal().save_config_to(&config_path)?;
         }
+        Config::save_schema_near(&config_path)?;
         println!("Config saved to {}", config_path.display());
     }
-    config_path.try_exists()?;
-    let config = read_to_string(&config_path)?;
-    let mut config = Config::try_from(config.as_str())?;
+    if let Some(Commands::Schema) = cli.command {
+        println!("{}", Config::schema());
+        return Ok(());
+    }
+    if let Some(Commands::Check) = cli.command {
+        return check_config(&config_path);
+    }
+    let mut config = Config::load(&config_path)?;
+    if cli.sandbox() {
+        config.set_sandbox(true);
+    }
+    if cli.command.as_ref().is_some_and(Commands::runs_programs) {
+        cancel_on_ctrlc(&config)?;
+    }
 
     let Some(command) = &cli.command else {
         return Ok(());
@@ -36,20 +77,213 @@ pub fn main(cli: &Cli) -> Result<()> {
     match command {
         Commands::Add => {
             ask_and_add_task(&mut config)?;
-            config.save_config_to(&config_path)?;
+            save_config(&mut config, &config_path)?;
             println!("Saved to {}", config_path.display());
         }
-        Commands::Test { id } => run_task_tests(&config, id)?,
-        Commands::Format => config.save_config_to(&config_path)?,
-        Commands::Init { .. } => (),
+        Commands::New { id, force } => {
+            let (config, _, id) = locate_task(config, &config_path, id)?;
+            let path = config.create_source(&id, *force)?;
+            println!("Created {}", path.display());
+        }
+        Commands::Test { id, profile } => {
+            let (mut config, _, id) = locate_task(config, &config_path, id)?;
+            config.set_profile(profile.clone());
+            run_task_tests(&config, &id, cli.verbose())?
+        }
+        Commands::Debug { id, n } => {
+            let (mut config, _, id) = locate_task(config, &config_path, id)?;
+            // Ctrl+C is handled by debugger
+            ctrlc::set_handler(|| ())?;
+            debug_task(&mut config, &id, *n, cli.verbose())?
+        }
+        Commands::Run { id, input, save } => {
+            let (mut config, config_path, id) = locate_task(config, &config_path, id)?;
+            let Some((input, output)) = run_task(&config, &id, input, cli.verbose())? else {
+                return Ok(());
+            };
+            print_run_output(&output);
+            if *save {
+                if output.is_runtime_error() {
+                    bail!("solution has runtime error, its output cannot be saved as expected");
+                }
+                config.check_input(&id, &input)?;
+                config.add_test_to_task(&id, input.as_str(), output.stdout.trim());
+                save_config(&mut config, &config_path)?;
+                println!("Saved to {}", config_path.display());
+            }
+        }
+        Commands::Accept { id, n } => {
+            let (mut config, config_path, id) = locate_task(config, &config_path, id)?;
+            if accept_task_outputs(&mut config, &id, *n, cli.verbose())? {
+                save_config(&mut config, &config_path)?;
+                println!("Saved to {}", config_path.display());
+            }
+        }
+        Commands::Bundle {
+            id,
+            strip_local,
+            no_verify,
+        } => {
+            let (config, _, id) = locate_task(config, &config_path, id)?;
+            let path = config.bundle(&id, *strip_local)?;
+            println!("Bundled to {}", path.display());
+            if !no_verify {
+                check_bundle(&config, &id, &path)?;
+            }
+        }
+        Commands::Submit {
+            id,
+            no_verify,
+            no_wait,
+        } => {
+            let (config, _, id) = locate_task(config, &config_path, id)?;
+            submit_task(&config, &id, *no_verify, *no_wait)?
+        }
+        Commands::Bench {
+            id,
+            repeat,
+            no_save,
+        } => {
+            let (config, _, id) = locate_task(config, &config_path, id)?;
+            bench_task(&config, &id, *repeat, *no_save, cli.verbose())?
+        }
+        Commands::Validate { id } => {
+            let (config, _, id) = locate_task(config, &config_path, id)?;
+            validate_tests(&config, &id)?
+        }
+        Commands::Hack {
+            id,
+            source,
+            attempts,
+            no_sandbox,
+        } => {
+            let (mut config, _, id) = locate_task(config, &config_path, id)?;
+            // foreign solutions are untrusted
+            if cfg!(target_os = "linux") && !no_sandbox {
+                config.set_sandbox(true);
+            }
+            hack_task(&config, &id, source, *attempts, cli.verbose())?
+        }
+        Commands::Show { id } => {
+            let (config, _, id) = locate_task(config, &config_path, id)?;
+            show_task(&config, &id)?
+        }
+        Commands::History { id, limit } => {
+            let (config, _, id) = locate_task(config, &config_path, id)?;
+            show_history(&config, &id, *limit)?
+        }
+        Commands::Contest {
+            command:
+                ContestCommands::Init {
+                    problems,
+                    from,
+                    id,
+                    name,
+                },
+        } => {
+            let ids = init_contest(&mut config, problems, from, id, name)?;
+            save_config(&mut config, &config_path)?;
+            println!("Saved to {}", config_path.display());
+            create_sources(&config, &ids);
+        }
+        Commands::Config {
+            command: ConfigCommands::Show,
+        } => show_config(&config, &config_path)?,
+        Commands::Format => {
+            config.format();
+            save_config(&mut config, &config_path)?
+        }
+        Commands::Init { .. } | Commands::Check | Commands::Schema => (),
     }
 
     Ok(())
 }
 
+/// Kill running build or test on first Ctrl+C, exit on second one
+fn cancel_on_ctrlc(config: &Config) -> Result<()> {
+    let token = config.cancel_token().clone();
+    ctrlc::set_handler(move || {
+        if token.is_cancelled() {
+            exit(INTERRUPTED_EXIT_CODE);
+        }
+        token.cancel();
+    })?;
+    Ok(())
+}
+
+/// Save config, merging changes made to the file since it was loaded
+fn save_config(config: &mut Config, path: &Path) -> Result<()> {
+    match config.save_config_to(path) {
+        Err(LibError::ConfigChangedOnDisk(_)) => {
+            println!("Config was changed on disk, merging changes");
+            config.merge_from_disk(path)?;
+            config.save_config_to(path)?;
+        }
+        r => r?,
+    }
+    Ok(())
+}
+
+/// Find config of task by address like `a` or `1850/a`, which can be in config of contest
+/// directory
+fn locate_task(
+    config: Config,
+    config_path: &Path,
+    address: &str,
+) -> Result<(Config, PathBuf, TaskID)> {
+    match config.locate_task(address) {
+        TaskLocation::Local(id) => Ok((config, config_path.to_path_buf(), id)),
+        TaskLocation::Workspace(path, id) => {
+            let mut workspace = Config::load(&path)?;
+            workspace.set_cancel_token(config.cancel_token().clone());
+            // sandbox of contest also applies to its tasks
+            if config.is_sandboxed() {
+                workspace.set_sandbox(true);
+            }
+            Ok((workspace, path, id))
+        }
+    }
+}
+
+fn check_config(config_path: &Path) -> Result<()> {
+    let diagnostics = Config::check(config_path)?;
+    for d in &diagnostics {
+        match d.location {
+            Some(_) => println!("{}:{d}", config_path.display()),
+            None => println!("{}: {d}", config_path.display()),
+        }
+    }
+    let errors = diagnostics.iter().filter(|d| d.is_error()).count();
+    let warnings = diagnostics.len() - errors;
+    if errors > 0 {
+        bail!("config has {errors} errors and {warnings} warnings");
+    }
+    println!("Config is valid, {warnings} warnings");
+    Ok(())
+}
+
+fn show_config(config: &Config, config_path: &Path) -> Result<()> {
+    println!("# project config: {}", config_path.display());
+    match config.user_config_path() {
+        Some(path) => println!("# user config: {}", path.display()),
+        None => println!("# user config: not found"),
+    }
+    for v in config.values()? {
+        let origin = match v.origin {
+            ValueOrigin::Project => "project",
+            ValueOrigin::User(_) => "user",
+            ValueOrigin::Default => "default",
+        };
+        println!("{} = {} # {origin}", v.key, v.value);
+    }
+    let tasks = config.tasks().count();
+    println!("# {tasks} tasks");
+    Ok(())
+}
+
 fn ask_and_add_task(config: &mut Config) -> Result<()> {
     let id = read_line_with_prompt("Enter task_id: ")?;
-    let id = id.trim().into();
+    let id = id.trim().to_lowercase();
     let name = match config.get_task_name(&id) {
         Some(n) => n,
         None => read_line_with_prompt("Enter task name: ")?,
@@ -57,6 +291,7 @@ fn ask_and_add_task(config: &mut Config) -> Result<()> {
     let continue_prompt = format!("(press {EOF_KEYBOARD} to continue)");
     let prompt = format!("Enter task input {continue_prompt}:\n");
     let input = read_until_eof_with_prompt(&prompt)?;
+    config.check_input(&id, &input)?;
 
     let prompt = format!("Enter expected output {continue_prompt}:\n");
     let expected = read_until_eof_with_prompt(&prompt)?;
@@ -65,54 +300,599 @@ fn ask_and_add_task(config: &mut Config) -> Result<()> {
     Ok(())
 }
 
-fn run_task_tests(config: &Config, id: &TaskID) -> Result<()> {
+/// Add tasks of contest from problem list and saved contest page. Returns ids of tasks
+fn init_contest(
+    config: &mut Config,
+    problems: &Option<String>,
+    from: &Option<PathBuf>,
+    id: &Option<String>,
+    name: &Option<String>,
+) -> Result<Vec<TaskID>> {
+    let page = match from {
+        Some(path) => ContestPage::parse(&read_to_string(path)?),
+        None => ContestPage::default(),
+    };
+    let problems = match problems {
+        Some(list) => {
+            let Some(ids) = parse_problem_ids(list) else {
+                bail!("invalid problem list \"{list}\", expected e.g. \"a-h\" or \"a,b,c1,c2\"");
+            };
+            ids.into_iter()
+                .map(|id| match page.problems.iter().find(|p| p.id == id) {
+                    Some(p) => p.clone(),
+                    None => Problem {
+                        id,
+                        ..Default::default()
+                    },
+                })
+                .collect()
+        }
+        None => page.problems,
+    };
+    if problems.is_empty() {
+        bail!("no problems found");
+    }
+    let id = id.clone().or(page.id);
+    let contest = Contest {
+        url: id.as_deref().map(contest_url),
+        id,
+        name: name.clone().or(page.name),
+        ..Default::default()
+    };
+    let ids = config.init_contest(contest, problems)?;
+    for t in config.tasks().filter(|t| ids.contains(t.id)) {
+        println!("{}", t.format());
+    }
+    Ok(ids)
+}
+
+/// Create sources of tasks from templates, skipping existing ones
+fn create_sources(config: &Config, ids: &[TaskID]) {
+    for id in ids {
+        match config.create_source(id, false) {
+            Ok(path) => println!("Created {}", path.display()),
+            Err(LibError::SourceExists(path)) => {
+                println!("Skipped existing {}", path.display())
+            }
+            Err(e) => {
+                println!("Sources are not created: {e}");
+                return;
+            }
+        }
+    }
+}
+
+fn run_task_tests(config: &Config, id: &TaskID, verbose: bool) -> Result<()> {
     config.check_task(id)?;
     println!(
         "Task {} - {}",
         id.to_uppercase(),
         config.get_task_name(id).unwrap_or("unnamed task".into())
     );
-    if config.should_build() {
-        println!("Building");
-        let output = config.build(id)?;
-        if let Some(output) = output {
-            if !output.success {
-                eprintln!("{}", output.stderr);
-                return Ok(());
+    let build = build_task_output(config, id, verbose)?;
+    if build.as_ref().is_some_and(|b| !b.success) {
+        return Ok(());
+    }
+    println!("Testing");
+    let mut results = vec![];
+    for res in config.run_tests(id) {
+        match res {
+            TestResult::Ok(_) => print!("."),
+            TestResult::Failed(_) => print!("x"),
+            TestResult::Err(e) => return Err(e.into()),
+            TestResult::Cancelled => return Err(LibError::Cancelled.into()),
+        }
+        results.push(res);
+    }
+    let failed: Vec<&FailedTest> = results
+        .iter()
+        .filter_map(|r| match r {
+            TestResult::Failed(f) => Some(f),
+            _ => None,
+        })
+        .collect();
+    if !failed.is_empty() {
+        println!(" failed\n");
+        failed.into_iter().for_each(print_failed_test);
+    } else {
+        println!(" ok");
+    }
+
+    let previous = config.history(id)?.pop();
+    let input_hashes = config.input_hashes(id);
+    let run = config.save_run(id, build.as_ref(), &results, &input_hashes)?;
+    if let Some(previous) = previous {
+        for change in run.changes(&previous) {
+            println!("{}", format_change(&change));
+        }
+    }
+    Ok(())
+}
+
+/// Build task with debug profile, if it is set, and run debugger with input of test `n`
+fn debug_task(config: &mut Config, id: &TaskID, n: usize, verbose: bool) -> Result<()> {
+    config.test(id, n)?;
+    if config.has_profile(id, DEBUG_PROFILE) {
+        config.set_profile(Some(DEBUG_PROFILE.into()));
+    } else {
+        println!("Profile \"{DEBUG_PROFILE}\" is not set, using build settings");
+    }
+    if !build_task(config, id, verbose)? {
+        return Ok(());
+    }
+    println!("Debugging test {n}");
+    if !config.debug(id, n)? {
+        bail!("debugger exited with error");
+    }
+    Ok(())
+}
+
+fn bench_task(
+    config: &Config,
+    id: &TaskID,
+    repeat: usize,
+    no_save: bool,
+    verbose: bool,
+) -> Result<()> {
+    config.check_task(id)?;
+    if !build_task(config, id, verbose)? {
+        return Ok(());
+    }
+    println!("Benchmarking, {repeat} runs of each test");
+    let baseline = config.baseline(id)?;
+    if baseline
+        .as_ref()
+        .is_some_and(|b| config.is_baseline_solution(id, b))
+    {
+        println!("Solution is not changed since baseline");
+    }
+    let threshold = config.bench_threshold();
+    let limit = config.time_limit(id);
+    let limit_warning = config.time_limit_warning(id);
+    let mut timings = vec![];
+    for (i, res) in config.bench(id, repeat).enumerate() {
+        let timing = match res {
+            BenchResult::Ok(t) => t,
+            BenchResult::Failed(f) => {
+                println!("test {} failed\n", i + 1);
+                print_failed_test(&f);
+                bail!("solution should pass tests to be benchmarked");
+            }
+            BenchResult::Err(e) => return Err(e.into()),
+        };
+        let ms = |us: u64| us as f64 / 1000.0;
+        let mut line = format!(
+            "test {}: {:.1} ms (min {:.1}, max {:.1}, ±{:.0}%)",
+            i + 1,
+            ms(timing.median_us),
+            ms(timing.min_us),
+            ms(timing.max_us),
+            timing.noise()
+        );
+        if let Some(base) = baseline.as_ref().and_then(|b| b.test(&timing)) {
+            line += &format!(
+                ", baseline {:.1} ms ({:+.0}%)",
+                ms(base.median_us),
+                timing.change(base)
+            );
+            if timing.is_slower(base, threshold) {
+                line += ", slower than baseline";
             }
         }
+        if let (Some(limit), Some(warning)) = (limit, limit_warning) {
+            if timing.median() >= warning {
+                line += &format!(", close to time limit {} ms", limit.as_millis());
+            }
+        }
+        println!("{line}");
+        timings.push(timing);
+    }
+    if !no_save {
+        config.save_baseline(id, timings)?;
+        println!("Saved as baseline");
+    }
+    Ok(())
+}
+
+/// Print tests of task: beginning of input of literal tests, and commands of generated ones
+fn show_task(config: &Config, id: &TaskID) -> Result<()> {
+    let Some(task) = config.tasks().find(|t| t.id == id) else {
+        return Err(LibError::TaskNotFound(id.clone()).into());
+    };
+    println!("Task {}", task.format());
+    for (i, test) in task.tests.iter().enumerate() {
+        let lines = test.input.trim().lines().count();
+        let first = test.input.trim().lines().next().unwrap_or_default();
+        let mut preview: String = first.chars().take(SHOW_INPUT_WIDTH).collect();
+        if lines > 1 || preview.len() < first.len() {
+            preview += &format!(" ... ({lines} lines)");
+        }
+        println!("test {}: {preview}", i + 1);
+    }
+    for (i, test) in task.generated.iter().enumerate() {
+        let state = if config.is_generated_cached(id, test) {
+            "cached"
+        } else {
+            "not generated yet"
+        };
+        println!(
+            "test {}: generated by `{}` ({state})",
+            task.tests.len() + i + 1,
+            test.command(id)
+        );
+    }
+    Ok(())
+}
+
+fn show_history(config: &Config, id: &TaskID, limit: usize) -> Result<()> {
+    let runs = config.history(id)?;
+    if runs.is_empty() {
+        println!("No runs of task {} in history", id.to_uppercase());
+        return Ok(());
+    }
+    let skip = runs.len().saturating_sub(limit);
+    for (i, run) in runs.iter().enumerate().skip(skip) {
+        let previous = i.checked_sub(1).map(|i| &runs[i]);
+        let changed = previous.is_some_and(|p| p.solution_hash != run.solution_hash);
+        let solution = match run.short_solution_hash() {
+            Some(hash) if changed => format!(", solution {hash} (changed)"),
+            Some(hash) => format!(", solution {hash}"),
+            None => String::new(),
+        };
+        let build = run
+            .build
+            .as_ref()
+            .map_or(String::new(), |b| match b.warnings {
+                0 => format!(", built in {} ms", b.time_ms),
+                n => format!(", built in {} ms with {n} warnings", b.time_ms),
+            });
+        println!(
+            "{} {} {}/{} passed{solution}{build}",
+            run.format_time(),
+            run.format_tests(),
+            run.passed(),
+            run.tests.len()
+        );
+        for change in previous.map(|p| run.changes(p)).unwrap_or_default() {
+            println!("  {}", format_change(&change));
+        }
+    }
+    Ok(())
+}
+
+/// Change of test verdict since previous run, e.g. `test 3: accepted -> wrong answer`
+fn format_change(change: &TestChange) -> String {
+    let before = change
+        .before
+        .as_ref()
+        .map_or("new".into(), |v| v.to_string());
+    let regression = if change.is_regression() {
+        " (regression)"
+    } else {
+        ""
+    };
+    format!(
+        "test {}: {before} -> {}{regression}",
+        change.index + 1,
+        change.after
+    )
+}
+
+/// Build and test bundled source apart from local headers
+fn check_bundle(config: &Config, id: &TaskID, path: &Path) -> Result<()> {
+    println!("Checking bundle");
+    let check = config.check_bundle(id, path)?;
+    if let Some(build) = check.build.as_ref().filter(|b| !b.success) {
+        eprintln!("{}", build.stderr);
+        bail!("bundle cannot be built");
     }
-    println!("Testing");
     let mut failed = vec![];
-    for res in config.run_tests(id) {
-        match res as TestResult {
-            TestResult::Ok => print!("."),
+    for res in check.results {
+        match res {
+            TestResult::Ok(_) => print!("."),
             TestResult::Failed(f) => {
                 print!("x");
                 failed.push(f);
             }
             TestResult::Err(e) => return Err(e.into()),
+            TestResult::Cancelled => return Err(LibError::Cancelled.into()),
         }
     }
     if !failed.is_empty() {
         println!(" failed\n");
         failed.iter().for_each(print_failed_test);
-    } else {
-        println!(" ok");
+        bail!("bundle does not pass tests");
     }
+    println!(" ok\nBundle is ready: {}", path.display());
+    Ok(())
+}
 
+/// Check inputs of tests with validator, printing messages for invalid ones
+fn validate_tests(config: &Config, id: &TaskID) -> Result<()> {
+    println!("Validating");
+    let invalid = config.check_tests(id)?;
+    for test in &invalid {
+        let kind = if test.generated {
+            "generated test"
+        } else {
+            "test"
+        };
+        println!("-- {kind} {} --\n{}", test.index + 1, test.message);
+    }
+    match invalid.len() {
+        0 => (),
+        1 => bail!("1 test is invalid"),
+        n => bail!("{n} tests are invalid"),
+    }
+    println!("All tests are valid");
+    Ok(())
+}
+
+/// Build task as reference, then search for input breaking solution from `source`
+fn hack_task(
+    config: &Config,
+    id: &TaskID,
+    source: &Path,
+    attempts: u64,
+    verbose: bool,
+) -> Result<()> {
+    config.check_task(id)?;
+    if !build_task(config, id, verbose)? {
+        bail!("task cannot be built");
+    }
+    println!("Hacking {}", source.display());
+    let failed = match config.hack(id, source, attempts)? {
+        HackResult::BuildFailed(output) => {
+            eprintln!("{}", output.stderr);
+            bail!("{} cannot be built", source.display());
+        }
+        HackResult::Passed { tests, generated } => {
+            println!("Solution passed {tests} tests and {generated} generated inputs");
+            return Ok(());
+        }
+        HackResult::FailsTest(failed) => {
+            print_failed_test(&failed);
+            failed
+        }
+        HackResult::FailsGenerated { seed, failed } => {
+            print_failure(&format!("generated input, seed {seed}"), &failed);
+            failed
+        }
+    };
+    let path = config.save_hack(id, &failed.input)?;
+    println!("\nHack input saved to {}", path.display());
+    Ok(())
+}
+
+/// Bundle, check and submit solution, then wait for verdict
+fn submit_task(config: &Config, id: &TaskID, no_verify: bool, no_wait: bool) -> Result<()> {
+    let target = config.submit_target(id)?;
+    let submitter = config.submitter()?;
+    let path = config.bundle(id, false)?;
+    println!("Bundled to {}", path.display());
+    if !no_verify {
+        check_bundle(config, id, &path)?;
+    }
+    let submission = submitter.submit(&target, &read_to_string(&path)?)?;
+    println!(
+        "Submitted to problem {}{}, submission {}",
+        target.contest, target.problem, submission.id
+    );
+    if no_wait {
+        return Ok(());
+    }
+    let start = Instant::now();
+    let mut submission = submission;
+    let verdict = loop {
+        if let Some(verdict) = submission.verdict {
+            break verdict;
+        }
+        if start.elapsed() > VERDICT_TIMEOUT {
+            println!();
+            bail!(
+                "verdict is not ready yet, check submission {} later",
+                submission.id
+            );
+        }
+        print!("\rTesting, passed {} tests", submission.passed_tests);
+        stdout().flush()?;
+        sleep(VERDICT_POLL_INTERVAL);
+        submission = submitter.status(&target, submission.id)?;
+    };
+    println!(
+        "\rVerdict: {verdict}, passed {} tests",
+        submission.passed_tests
+    );
     Ok(())
 }
 
+/// Run tests and replace expected output with actual output. Returns `true` if any test was
+/// changed
+fn accept_task_outputs(
+    config: &mut Config,
+    id: &TaskID,
+    n: Option<usize>,
+    verbose: bool,
+) -> Result<bool> {
+    config.check_task(id)?;
+    if let Some(n) = n {
+        let count = config
+            .tasks()
+            .find(|t| t.id == id)
+            .map_or(0, |t| t.tests.len());
+        if n == 0 || n > count {
+            bail!("task {} has no test {n}", id.to_uppercase());
+        }
+    }
+    if !build_task(config, id, verbose)? {
+        return Ok(false);
+    }
+    println!("Testing");
+    let results: Vec<TestResult> = config.run_tests(id).into_iter().collect();
+    let mut accepted = false;
+    // tests skipped because they are generated, or have runtime error
+    let (mut generated, mut crashed) = (0, 0);
+    for (i, res) in results.into_iter().enumerate() {
+        if n.is_some_and(|n| n != i + 1) {
+            continue;
+        }
+        match res {
+            TestResult::Ok(_) => (),
+            TestResult::Failed(f) if f.generated => {
+                println!("test {} is generated, it cannot be accepted", f.index + 1);
+                generated += 1;
+            }
+            TestResult::Failed(f) if f.is_runtime_error() => {
+                println!(
+                    "test {} has runtime error, it cannot be accepted",
+                    f.index + 1
+                );
+                crashed += 1;
+            }
+            TestResult::Failed(f) => {
+                config.update_test(id, f.index, f.accepted());
+                println!("test {} accepted", f.index + 1);
+                accepted = true;
+            }
+            TestResult::Err(e) => return Err(e.into()),
+            TestResult::Cancelled => return Err(LibError::Cancelled.into()),
+        }
+    }
+    let mut skipped = vec![];
+    if generated > 0 {
+        skipped.push(format!(
+            "{generated} generated (expected output is printed by reference)"
+        ));
+    }
+    if crashed > 0 {
+        skipped.push(format!("{crashed} with runtime error"));
+    }
+    if !skipped.is_empty() {
+        let prefix = if accepted {
+            "Skipped"
+        } else {
+            "Nothing accepted, skipped"
+        };
+        println!("{prefix} failed tests: {}", skipped.join(", "));
+    } else if !accepted {
+        println!("Nothing to accept, output already matches");
+    }
+    Ok(accepted)
+}
+
+/// Build task, printing build errors. Returns `false` if build failed
+fn build_task(config: &Config, id: &TaskID, verbose: bool) -> Result<bool> {
+    let output = build_task_output(config, id, verbose)?;
+    Ok(output.is_none_or(|o| o.success))
+}
+
+/// Build task if it has build command, printing errors, and warnings if `verbose`
+fn build_task_output(config: &Config, id: &TaskID, verbose: bool) -> Result<Option<CommandOutput>> {
+    if !config.should_build(id) {
+        return Ok(None);
+    }
+    println!("Building");
+    let Some(output) = config.build(id)? else {
+        return Ok(None);
+    };
+    let diagnostics = config.build_diagnostics(id, &output);
+    if !output.success && diagnostics.is_empty() {
+        // unknown format of errors
+        eprintln!("{}", output.stderr);
+        return Ok(Some(output));
+    }
+    if !output.success || verbose {
+        print_diagnostics(&diagnostics);
+    }
+    let warnings = diagnostics.len() - diagnostics.iter().filter(|d| d.is_error()).count();
+    if !output.success {
+        if warnings == diagnostics.len() {
+            eprintln!("{}", color::red("warnings are treated as errors"));
+        }
+    } else if warnings > 0 && !verbose {
+        println!(
+            "Built in {:.1}s, {warnings} warnings (use --verbose to show them)",
+            output.time.as_secs_f64()
+        );
+    } else {
+        println!("Built in {:.1}s", output.time.as_secs_f64());
+    }
+    Ok(Some(output))
+}
+
+/// One line per diagnostic, e.g. `a.cpp:3:5: error: message`, with count of them
+fn print_diagnostics(diagnostics: &[BuildDiagnostic]) {
+    if diagnostics.is_empty() {
+        return;
+    }
+    let cwd = current_dir().unwrap_or_default();
+    for d in diagnostics {
+        let severity = if d.is_error() {
+            color::red(&d.severity.to_string())
+        } else {
+            color::yellow(&d.severity.to_string())
+        };
+        let location = d.location.as_ref().map_or(String::new(), |l| {
+            let file = l.file.strip_prefix(&cwd).unwrap_or(&l.file);
+            match l.column {
+                Some(column) => format!("{}:{}:{column}: ", file.display(), l.line),
+                None => format!("{}:{}: ", file.display(), l.line),
+            }
+        });
+        eprintln!("{location}{severity}: {}", d.message);
+    }
+    let errors = diagnostics.iter().filter(|d| d.is_error()).count();
+    let warnings = diagnostics.len() - errors;
+    eprintln!("{errors} errors, {warnings} warnings");
+}
+
+/// Build task and run it with input from file or stdin. Returns `None` if build failed
+fn run_task(
+    config: &Config,
+    id: &TaskID,
+    input: &Option<PathBuf>,
+    verbose: bool,
+) -> Result<Option<(String, CommandOutput)>> {
+    if !build_task(config, id, verbose)? {
+        return Ok(None);
+    }
+    let input = match input {
+        Some(path) => read_to_string(path)?,
+        None if stdin().is_terminal() => {
+            let prompt = format!("Enter input (press {EOF_KEYBOARD} to continue):\n");
+            read_until_eof_with_prompt(&prompt)?
+        }
+        // input is piped or passed with heredoc
+        None => read_until_eof_with_prompt("")?,
+    };
+    let output = config.run(id, input.clone())?;
+    Ok(Some((input, output)))
+}
+
+fn print_run_output(output: &CommandOutput) {
+    println!("Output:\n{}", output.stdout.trim_end());
+    if !output.stderr.is_empty() {
+        println!("Stderr:\n{}", output.stderr.trim_end());
+    }
+    let status = if output.success { "ok" } else { "failed" };
+    println!("Finished in {:.3}s, {status}", output.time.as_secs_f64());
+}
+
 fn print_failed_test(f: &FailedTest) {
+    print_failure(&format!("test {}, {}", f.index + 1, f.verdict()), f)
+}
+
+fn print_failure(title: &str, f: &FailedTest) {
+    let mut crash = String::new();
+    if let Some(report) = &f.crash {
+        crash = format!("{}\n\n", color::red(&report.to_string()));
+    }
     let mut stderr = String::new();
     if !f.cmd_output.stderr.is_empty() {
         stderr = format!("\nStderr:\n{}", f.cmd_output.stderr);
     }
     println!(
-        "-- test {} --\nExpected output:\n{}\n\nActual output:\n{}{stderr}",
-        f.index + 1,
-        f.expected,
-        f.cmd_output.stdout
+        "-- {title} --\n{crash}Expected output:\n{}\n\nActual output:\n{}{stderr}",
+        f.expected, f.cmd_output.stdout
     );
 }
@@ -1,17 +1,24 @@
-use std::{fs::read_to_string, path::PathBuf};
+use std::{collections::BTreeMap, path::PathBuf, time::Duration};
 
-use eframe::egui::{self, Link, RichText, Ui};
+use eframe::egui::{self, Color32, Link, ProgressBar, RichText, Ui};
 use rfd::FileDialog;
 
-use lib::{Config, Error as LibError, TaskID, TestResult};
+use lib::{
+    BuildDiagnostic, CommandOutput, Config, Error as LibError, Run, SourceLocation, TaskID,
+    TaskInfo, Test, TestChange, TestResult,
+};
 
+use crate::editor::open_at;
 use crate::errors::{Error, ErrorKind, ErrorsMap};
 use crate::widgets::{
     add_task, add_test, edit_task, edit_tests, AddTaskState, AddTestState, EditTaskState,
     EditTestsResponse, EditTestsState,
 };
+use crate::worker::{TestsEvent, TestsWorker};
 
 pub(crate) const CONFIG_PATH_STORAGE_KEY: &str = "config_path";
+/// Interval of updating elapsed time while tests are running
+const PROGRESS_REPAINT_INTERVAL: Duration = Duration::from_millis(100);
 
 #[derive(Debug, Default)]
 pub(crate) struct App {
@@ -20,6 +27,10 @@ pub(crate) struct App {
     app_state: AppState,
     post_update: PostUpdate,
     errors: ErrorsMap,
+    tests_worker: Option<TestsWorker>,
+    /// Output of last build, with errors and warnings found in it
+    build_output: Option<CommandOutput>,
+    build_diagnostics: Vec<BuildDiagnostic>,
 }
 
 impl App {
@@ -38,7 +49,12 @@ enum AppState {
     AddTest(TaskID, AddTestState),
     EditTests(TaskID, EditTestsState),
 
-    ShowTestsResults(Vec<TestResult>),
+    /// Tests are running in [`TestsWorker`]
+    RunningTests,
+    /// Results with changes of verdicts since previous run
+    ShowTestsResults(TaskID, Vec<TestResult>, Vec<TestChange>),
+    ShowHistory(TaskID, Vec<Run>),
+    ConfigChangedOnDisk,
     Msg(String),
     #[default]
     None,
@@ -47,25 +63,40 @@ enum AppState {
 #[derive(Debug, Default)]
 enum PostUpdate {
     SaveConfig,
+    MergeConfig,
+    OverwriteConfig,
     OpenConfigInEditor,
+    OpenConfig(PathBuf),
+    /// Open file in editor at location of build diagnostic
+    OpenSource(SourceLocation),
     CancelOperation,
     RunTests(TaskID),
+    CancelTests,
+    ShowHistory(TaskID),
+    /// Save config and create solution source of added task
+    CreateSource(TaskID),
+    AcceptTestOutput(TaskID, usize, Test),
     #[default]
     None,
 }
 
 impl eframe::App for App {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        self.poll_tests_worker();
+        if self.tests_worker.is_some() {
+            ctx.request_repaint_after(PROGRESS_REPAINT_INTERVAL);
+        }
         egui::CentralPanel::default().show(ctx, |ui| {
             egui::ScrollArea::both()
                 .auto_shrink([false; 2])
                 .show(ui, |ui| {
                     self.config_select_ui(ui);
                     self.config_content_ui(ui);
+                    self.build_ui(ui);
                     self.app_state_ui(ui);
                     self.errors_ui(ui);
 
-                    self.handle_post_update();
+                    self.handle_post_update(ctx);
                 });
         });
     }
@@ -117,28 +148,45 @@ impl App {
 
         if let Some(config) = &self.config {
             ui.heading("Tasks");
+            let mut contests: BTreeMap<Option<&str>, Vec<TaskInfo>> = BTreeMap::new();
             for t in config.tasks() {
-                ui.horizontal(|ui| {
-                    if ui.button("edit").clicked() {
-                        self.app_state =
-                            AppState::EditTask(t.id.clone(), EditTaskState::new(t.id, t.name));
-                    }
-                    if ui.button("add test").clicked() {
-                        self.app_state = AppState::AddTest(t.id.clone(), AddTestState::default());
-                    }
-                    if ui.button("edit tests").clicked() {
-                        self.app_state =
-                            AppState::EditTests(t.id.clone(), EditTestsState::new(t.id, t.tests));
-                    }
-                    if ui.button(RichText::new("run tests").strong()).clicked() {
-                        self.post_update = PostUpdate::RunTests(t.id.clone());
-                    }
-                    ui.label(RichText::new(t.format()).strong());
-                });
+                contests.entry(t.contest).or_default().push(t);
+            }
+            for (contest, tasks) in contests {
+                if let Some(id) = contest {
+                    let contest = config.contest(id).cloned().unwrap_or_default();
+                    ui.horizontal(|ui| {
+                        ui.label(RichText::new(contest.format(id)).heading());
+                        if let Some(url) = &contest.url {
+                            ui.hyperlink_to("open", url);
+                        }
+                    });
+                }
+                for t in tasks {
+                    task_ui(ui, &t, &mut self.app_state, &mut self.post_update);
+                }
             }
             if ui.button("Add task").clicked() {
                 self.app_state = AppState::AddTask(AddTaskState::default());
             }
+
+            let contest_configs = config.contest_configs();
+            if !contest_configs.is_empty() {
+                ui.heading("Contest directories");
+            }
+            for (id, path) in contest_configs {
+                ui.horizontal(|ui| {
+                    if ui
+                        .button("open")
+                        .on_hover_text("Open config of contest")
+                        .clicked()
+                    {
+                        self.post_update = PostUpdate::OpenConfig(path.clone());
+                    }
+                    let contest = config.contest(id).cloned().unwrap_or_default();
+                    ui.label(RichText::new(contest.format(id)).strong());
+                });
+            }
         }
     }
     fn app_state_ui(&mut self, ui: &mut Ui) {
@@ -147,7 +195,11 @@ impl App {
                 if ui.add(add_task(state)).clicked() {
                     if let Some(ref mut config) = self.config {
                         config.add_task(&state.id, &state.name);
-                        self.post_update = PostUpdate::SaveConfig;
+                        self.post_update = if state.create_source {
+                            PostUpdate::CreateSource(state.id.to_lowercase())
+                        } else {
+                            PostUpdate::SaveConfig
+                        };
                     }
                 }
             }
@@ -162,8 +214,14 @@ impl App {
             AppState::AddTest(task_id, ref mut state) => {
                 if ui.add(add_test(state, task_id.clone())).clicked() {
                     if let Some(ref mut config) = self.config {
-                        config.add_test_to_task(task_id, &state.input, &state.expected);
-                        self.post_update = PostUpdate::SaveConfig;
+                        self.errors.delete(ErrorKind::InvalidTestInput);
+                        match config.check_input(task_id, &state.input) {
+                            Ok(()) => {
+                                config.add_test_to_task(task_id, &state.input, &state.expected);
+                                self.post_update = PostUpdate::SaveConfig;
+                            }
+                            Err(e) => self.errors.add(Error::InvalidTestInput(e.to_string())),
+                        }
                     }
                 }
             }
@@ -180,44 +238,180 @@ impl App {
                     EditTestsResponse::None => (),
                 }
             }
-            AppState::ShowTestsResults(results) => {
-                for (i, res) in results.iter().enumerate() {
-                    match res {
-                        TestResult::Ok => {
-                            ui.label(format!("test {} ok", i + 1));
+            AppState::RunningTests => {
+                if let Some(worker) = &self.tests_worker {
+                    let done = worker.results.len();
+                    ui.horizontal(|ui| {
+                        let stage = if worker.building {
+                            "Building"
+                        } else {
+                            "Testing"
+                        };
+                        ui.strong(format!("{stage} task {}", worker.id.to_uppercase()));
+                        ui.label(format!("{:.1} s", worker.elapsed().as_secs_f32()));
+                        if ui
+                            .button("Cancel")
+                            .on_hover_text("Kill running program")
+                            .clicked()
+                        {
+                            self.post_update = PostUpdate::CancelTests;
                         }
-                        TestResult::Failed(f) => {
-                            ui.collapsing(format!("test {} failed:", f.index + 1), |ui| {
-                                ui.horizontal(|ui| {
-                                    ui.vertical(|ui| {
-                                        ui.strong("Expected output:");
-                                        ui.monospace(&f.expected);
-                                    });
-                                    ui.separator();
-                                    ui.vertical(|ui| {
-                                        ui.strong("Actual output:");
-                                        ui.monospace(f.cmd_output.stdout.trim());
-                                    });
-                                });
-                                if !f.cmd_output.stderr.is_empty() {
-                                    ui.strong("Stderr:");
-                                    ui.monospace(f.cmd_output.stderr.trim());
-                                }
-                            });
+                    });
+                    let progress = done as f32 / worker.total.max(1) as f32;
+                    ui.add(ProgressBar::new(progress).text(format!("{done}/{}", worker.total)));
+                    results_ui(
+                        ui,
+                        &worker.id,
+                        &worker.results,
+                        &mut self.post_update,
+                        &mut self.errors,
+                    );
+                }
+            }
+            AppState::ShowTestsResults(task_id, results, changes) => {
+                results_ui(
+                    ui,
+                    task_id,
+                    results,
+                    &mut self.post_update,
+                    &mut self.errors,
+                );
+                if !changes.is_empty() {
+                    ui.strong("Changes since previous run:");
+                    changes.iter().for_each(|c| change_ui(ui, c));
+                }
+            }
+            AppState::ShowHistory(task_id, runs) => {
+                ui.heading(format!("History of task {}", task_id.to_uppercase()));
+                if runs.is_empty() {
+                    ui.label("No runs yet");
+                }
+                // newest first
+                for (i, run) in runs.iter().enumerate().rev() {
+                    let previous = i.checked_sub(1).map(|i| &runs[i]);
+                    let mut title = format!(
+                        "{} {} {}/{} passed",
+                        run.format_time(),
+                        run.format_tests(),
+                        run.passed(),
+                        run.tests.len()
+                    );
+                    if let Some(hash) = run.short_solution_hash() {
+                        title += &format!(", solution {hash}");
+                    }
+                    if let Some(build) = &run.build {
+                        title += &format!(", built in {} ms", build.time_ms);
+                        if build.warnings > 0 {
+                            title += &format!(" with {} warnings", build.warnings);
                         }
-                        TestResult::Err(e) => {
-                            self.errors.add(Error::CannotRunTests(e.to_string()));
-                            break;
+                    }
+                    let changes = previous.map(|p| run.changes(p)).unwrap_or_default();
+                    let mut title = RichText::new(title).monospace();
+                    if changes.iter().any(TestChange::is_regression) {
+                        title = title.color(Color32::RED);
+                    }
+                    ui.collapsing(title, |ui| {
+                        for (j, t) in run.tests.iter().enumerate() {
+                            let time = t.time_ms.map_or(String::new(), |ms| format!(", {ms} ms"));
+                            ui.label(format!("test {}: {}{time}", j + 1, t.verdict));
                         }
-                    };
+                        if !changes.is_empty() {
+                            ui.strong("Changes since previous run:");
+                            changes.iter().for_each(|c| change_ui(ui, c));
+                        }
+                    });
                 }
             }
+            AppState::ConfigChangedOnDisk => {
+                ui.label("Config was changed on disk since it was loaded");
+                ui.horizontal(|ui| {
+                    if ui
+                        .button("Reload")
+                        .on_hover_text("Discard changes made here")
+                        .clicked()
+                    {
+                        self.config = None;
+                    }
+                    if ui
+                        .button("Merge")
+                        .on_hover_text("Apply changes made here on top of config on disk")
+                        .clicked()
+                    {
+                        self.post_update = PostUpdate::MergeConfig;
+                    }
+                    if ui
+                        .button("Overwrite")
+                        .on_hover_text("Discard changes made on disk")
+                        .clicked()
+                    {
+                        self.post_update = PostUpdate::OverwriteConfig;
+                    }
+                });
+            }
             AppState::Msg(msg) => {
                 ui.label(msg.clone());
             }
             AppState::None => (),
         }
     }
+    fn build_ui(&mut self, ui: &mut Ui) {
+        let Some(output) = &self.build_output else {
+            return;
+        };
+        let errors = self
+            .build_diagnostics
+            .iter()
+            .filter(|d| d.is_error())
+            .count();
+        let warnings = self.build_diagnostics.len() - errors;
+        let mut clear = false;
+        ui.horizontal(|ui| {
+            let status = if output.success { "ok" } else { "failed" };
+            let heading = format!(
+                "Build {status} in {:.1} s: {errors} errors, {warnings} warnings",
+                output.time.as_secs_f32()
+            );
+            if output.success {
+                ui.heading(heading);
+            } else {
+                ui.heading(RichText::new(heading).color(Color32::RED));
+            }
+            clear = ui.button("Clear").clicked();
+        });
+        for d in &self.build_diagnostics {
+            let color = if d.is_error() {
+                Color32::RED
+            } else {
+                Color32::YELLOW
+            };
+            ui.horizontal(|ui| {
+                ui.label(RichText::new(d.severity.to_string()).color(color));
+                if let Some(location) = &d.location {
+                    let name = location.file.file_name().unwrap_or_default();
+                    let text = format!("{}:{}", name.to_string_lossy(), location.line);
+                    if ui
+                        .link(RichText::new(text).monospace())
+                        .on_hover_text(location.to_string())
+                        .clicked()
+                    {
+                        self.post_update = PostUpdate::OpenSource(location.clone());
+                    }
+                }
+                ui.label(&d.message);
+            });
+        }
+        ui.collapsing("Build log", |ui| {
+            for log in [&output.stdout, &output.stderr] {
+                if !log.trim().is_empty() {
+                    ui.monospace(log.trim_end());
+                }
+            }
+        });
+        if clear {
+            self.build_output = None;
+            self.build_diagnostics.clear();
+        }
+    }
     fn errors_ui(&mut self, ui: &mut Ui) {
         if !self.errors.is_empty() {
             let mut skip_show_errors = false;
@@ -242,6 +436,108 @@ impl App {
     }
 }
 
+/// Results of tests, with button to accept actual output of failed ones
+fn results_ui(
+    ui: &mut Ui,
+    task_id: &TaskID,
+    results: &[TestResult],
+    post_update: &mut PostUpdate,
+    errors: &mut ErrorsMap,
+) {
+    for (i, res) in results.iter().enumerate() {
+        match res {
+            TestResult::Ok(_) => {
+                ui.label(format!("test {} ok", i + 1));
+            }
+            TestResult::Failed(f) => {
+                let title = match &f.crash {
+                    Some(report) => format!("test {} failed: {}", f.index + 1, report.kind),
+                    None => format!("test {} failed: {}", f.index + 1, f.verdict()),
+                };
+                ui.collapsing(title, |ui| {
+                    if let Some(report) = &f.crash {
+                        ui.label(RichText::new(&report.summary).color(Color32::RED).strong());
+                        if let Some(frame) = &report.frame {
+                            ui.monospace(format!("at {frame}"));
+                        }
+                        ui.separator();
+                    }
+                    ui.horizontal(|ui| {
+                        ui.vertical(|ui| {
+                            ui.strong("Expected output:");
+                            ui.monospace(&f.expected);
+                        });
+                        ui.separator();
+                        ui.vertical(|ui| {
+                            ui.strong("Actual output:");
+                            ui.monospace(f.cmd_output.stdout.trim());
+                        });
+                    });
+                    if !f.cmd_output.stderr.is_empty() {
+                        ui.strong("Stderr:");
+                        ui.monospace(f.cmd_output.stderr.trim());
+                    }
+                    if !f.generated
+                        && !f.is_runtime_error()
+                        && ui
+                            .button("accept actual output")
+                            .on_hover_text("Save actual output as expected")
+                            .clicked()
+                    {
+                        *post_update =
+                            PostUpdate::AcceptTestOutput(task_id.clone(), f.index, f.accepted());
+                    }
+                });
+            }
+            TestResult::Err(e) => {
+                errors.add(Error::CannotRunTests(e.to_string()));
+                break;
+            }
+            TestResult::Cancelled => {
+                ui.label(format!("test {} cancelled", i + 1));
+            }
+        };
+    }
+}
+
+/// Change of test verdict, regressions are highlighted
+fn change_ui(ui: &mut Ui, change: &TestChange) {
+    let before = change
+        .before
+        .as_ref()
+        .map_or("new".into(), |v| v.to_string());
+    let text = format!("test {}: {before} -> {}", change.index + 1, change.after);
+    if change.is_regression() {
+        ui.colored_label(Color32::RED, text + " (regression)");
+    } else {
+        ui.label(text);
+    }
+}
+
+fn task_ui(ui: &mut Ui, t: &TaskInfo, app_state: &mut AppState, post_update: &mut PostUpdate) {
+    ui.horizontal(|ui| {
+        if ui.button("edit").clicked() {
+            *app_state = AppState::EditTask(t.id.clone(), EditTaskState::new(t.id, t.name));
+        }
+        if ui.button("add test").clicked() {
+            *app_state = AppState::AddTest(t.id.clone(), AddTestState::default());
+        }
+        if ui.button("edit tests").clicked() {
+            *app_state = AppState::EditTests(
+                t.id.clone(),
+                EditTestsState::new(t.id, t.tests, t.generated),
+            );
+        }
+        if ui.button(RichText::new("run tests").strong()).clicked() {
+            *post_update = PostUpdate::RunTests(t.id.clone());
+        }
+        if ui.button("history").clicked() {
+            *post_update = PostUpdate::ShowHistory(t.id.clone());
+        }
+        ui.label(RichText::new(t.format()).strong());
+    });
+}
+
 /// Logic
 impl App {
     fn select_config(&mut self) {
@@ -274,8 +570,13 @@ impl App {
             return self.errors.add(Error::CannotSelectPathForSavingConfig);
         };
 
+        self.errors.delete(ErrorKind::CannotSaveConfig);
+        if let Err(e) = Config::save_schema_near(&path) {
+            self.errors.add(Error::CannotSaveConfig(e.to_string()));
+        }
+
         self.config_path = Some(path);
-        self.config = Some(Config::default());
+        self.config = Some(Config::minimal());
         self.save_config();
         // read again to make sure there are no new errors, and delete old errors
         self.read_config();
@@ -290,14 +591,12 @@ impl App {
             };
 
             self.errors.delete(ErrorKind::CannotReadConfig);
-            let s = match read_to_string(path) {
-                Ok(s) => s,
-                Err(e) => return self.errors.add(Error::CannotReadConfig(e.to_string())),
-            };
-
             self.errors.delete(ErrorKind::CannotParseConfig);
-            let config = match Config::try_from(s.as_str()) {
+            let config = match Config::load(path) {
                 Ok(c) => c,
+                Err(LibError::CannotReadConfig(e)) => {
+                    return self.errors.add(Error::CannotReadConfig(e.to_string()))
+                }
                 Err(e) => {
                     return self
                         .errors
@@ -305,27 +604,63 @@ impl App {
                 }
             };
 
+            self.errors.delete(ErrorKind::ConfigDiagnostics);
+            let diagnostics = config.validate();
+            if !diagnostics.is_empty() {
+                let diagnostics = diagnostics.iter().map(|d| d.to_string()).collect();
+                self.errors.add(Error::ConfigDiagnostics(diagnostics));
+            }
+
             self.config = Some(config);
             self.clear_app_state();
         }
     }
     fn save_config(&mut self) {
+        self.write_config(false)
+    }
+    fn overwrite_config(&mut self) {
+        self.write_config(true)
+    }
+    fn write_config(&mut self, overwrite: bool) {
         self.errors.delete(ErrorKind::BugConfigPathEmpty);
         let Some(config_path) = &self.config_path else {
             return self.errors.add(Error::BugConfigPathEmpty);
         };
 
         self.errors.delete(ErrorKind::BugConfigEmpty);
-        let Some(config) = &self.config else {
+        let Some(config) = &mut self.config else {
             return self.errors.add(Error::BugConfigEmpty);
         };
 
         self.errors.delete(ErrorKind::CannotSaveConfig);
-        match config.save_config_to(config_path) {
+        let res = if overwrite {
+            config.overwrite_config_to(config_path)
+        } else {
+            config.save_config_to(config_path)
+        };
+        match res {
             Ok(_) => self.app_state = AppState::Msg("Config saved".into()),
+            Err(LibError::ConfigChangedOnDisk(_)) => self.app_state = AppState::ConfigChangedOnDisk,
             Err(e) => self.errors.add(Error::CannotSaveConfig(e.to_string())),
         }
     }
+    fn merge_config(&mut self) {
+        self.errors.delete(ErrorKind::BugConfigPathEmpty);
+        let Some(config_path) = &self.config_path else {
+            return self.errors.add(Error::BugConfigPathEmpty);
+        };
+
+        self.errors.delete(ErrorKind::BugConfigEmpty);
+        let Some(config) = &mut self.config else {
+            return self.errors.add(Error::BugConfigEmpty);
+        };
+
+        self.errors.delete(ErrorKind::CannotMergeConfig);
+        match config.merge_from_disk(config_path) {
+            Ok(_) => self.save_config(),
+            Err(e) => self.errors.add(Error::CannotMergeConfig(e.to_string())),
+        }
+    }
     fn open_config_in_editor(&mut self) {
         self.errors.delete(ErrorKind::BugConfigEmpty);
         let Some(config_path) = &self.config_path else {
@@ -346,17 +681,18 @@ impl App {
         }
     }
 
-    fn run_tests(&mut self, id: TaskID) {
+    fn open_source(&mut self, location: &SourceLocation) {
+        self.errors.delete(ErrorKind::CannotOpenSource);
+        if let Err(e) = open_at(location) {
+            self.errors.add(Error::CannotOpenSource(e.to_string()));
+        }
+    }
+    fn run_tests(&mut self, id: TaskID, ctx: &egui::Context) {
         self.errors.delete(ErrorKind::BugConfigEmpty);
         let Some(config) = &self.config else {
             return self.errors.add(Error::BugConfigEmpty);
         };
 
-        self.errors.delete(ErrorKind::BugConfigPathEmpty);
-        let Some(config_path) = &self.config_path else {
-            return self.errors.add(Error::BugConfigPathEmpty);
-        };
-
         match config.check_task(&id) {
             Ok(_) => (),
             Err(e) => {
@@ -366,34 +702,171 @@ impl App {
             }
         }
 
-        let dir = config_path.parent().map(|p| p.into());
-
+        if let Some(worker) = self.tests_worker.take() {
+            worker.cancel();
+        }
         self.errors.delete(ErrorKind::CannotBuildTask);
-        if config.should_build() {
-            match config.build_from_dir(&id, &dir) {
-                Ok(output) => {
-                    if let Some(output) = output {
-                        if !output.success {
-                            return self
-                                .errors
-                                .add(Error::CannotBuildTask("\n".to_owned() + &output.stderr));
-                        }
+        self.errors.delete(ErrorKind::ErrorRunningTest);
+        self.build_output = None;
+        self.build_diagnostics.clear();
+        let total = config
+            .tasks()
+            .find(|t| *t.id == id)
+            .map_or(0, |t| t.tests.len() + t.generated.len());
+        let worker = TestsWorker::spawn(config.clone(), id, total, ctx.clone());
+        self.tests_worker = Some(worker);
+        self.app_state = AppState::RunningTests;
+    }
+    /// Collect results from running worker, and show them when it finishes
+    fn poll_tests_worker(&mut self) {
+        let Some(worker) = &mut self.tests_worker else {
+            return;
+        };
+        let mut finished = false;
+        for event in worker.poll() {
+            match event {
+                TestsEvent::Built(output) => {
+                    let Some(config) = &self.config else {
+                        // other events, e.g. finishing of worker, must be handled
+                        self.errors.add(Error::BugConfigEmpty);
+                        continue;
+                    };
+                    self.build_diagnostics = config.build_diagnostics(&worker.id, &output);
+                    if !output.success && self.build_diagnostics.is_empty() {
+                        // unknown format of errors
+                        self.errors
+                            .add(Error::CannotBuildTask("\n".to_owned() + &output.stderr));
                     }
+                    self.build_output = Some(output);
                 }
-                Err(e) => return self.errors.add(Error::CannotBuildTask(e.to_string())),
+                TestsEvent::BuildError(LibError::Cancelled) => (),
+                TestsEvent::BuildError(e) => self.errors.add(Error::CannotBuildTask(e.to_string())),
+                TestsEvent::Finished => finished = true,
+            }
+        }
+        if !finished {
+            return;
+        }
+        let Some(worker) = self.tests_worker.take() else {
+            return;
+        };
+        if !matches!(self.app_state, AppState::RunningTests) {
+            // user switched to something else while tests were running
+            return;
+        }
+        if worker.building {
+            // build failed or was cancelled
+            self.app_state = if worker.is_cancelled() {
+                AppState::Msg("Build cancelled".into())
+            } else {
+                AppState::None
+            };
+            return;
+        }
+        let results = worker.results;
+        if results.iter().any(|r| matches!(r, TestResult::Cancelled)) {
+            // partial runs are not saved to history
+            return self.app_state = AppState::ShowTestsResults(worker.id, results, vec![]);
+        }
+        let Some(config) = &self.config else {
+            return self.errors.add(Error::BugConfigEmpty);
+        };
+        self.errors.delete(ErrorKind::CannotUpdateHistory);
+        let previous = config.history(&worker.id).map(|mut runs| runs.pop());
+        let changes = match (
+            previous,
+            config.save_run(
+                &worker.id,
+                self.build_output.as_ref(),
+                &results,
+                &worker.input_hashes,
+            ),
+        ) {
+            (Ok(previous), Ok(run)) => previous.map(|p| run.changes(&p)).unwrap_or_default(),
+            (Err(e), _) | (_, Err(e)) => {
+                self.errors.add(Error::CannotUpdateHistory(e.to_string()));
+                vec![]
             }
+        };
+        self.app_state = AppState::ShowTestsResults(worker.id, results, changes);
+    }
+    fn cancel_tests(&mut self) {
+        if let Some(worker) = &self.tests_worker {
+            worker.cancel();
+        }
+    }
+    fn show_history(&mut self, id: TaskID) {
+        self.errors.delete(ErrorKind::BugConfigEmpty);
+        let Some(config) = &self.config else {
+            return self.errors.add(Error::BugConfigEmpty);
+        };
+
+        self.errors.delete(ErrorKind::CannotUpdateHistory);
+        match config.history(&id) {
+            Ok(runs) => self.app_state = AppState::ShowHistory(id, runs),
+            Err(e) => self.errors.add(Error::CannotUpdateHistory(e.to_string())),
+        }
+    }
+    fn create_source(&mut self, id: TaskID) {
+        self.save_config();
+        if !matches!(self.app_state, AppState::Msg(_)) {
+            // config was not saved
+            return;
         }
+        self.errors.delete(ErrorKind::BugConfigEmpty);
+        let Some(config) = &self.config else {
+            return self.errors.add(Error::BugConfigEmpty);
+        };
 
-        let results: Vec<TestResult> = config.run_tests_from_dir(&id, &dir).into_iter().collect();
-        self.app_state = AppState::ShowTestsResults(results);
+        self.errors.delete(ErrorKind::CannotCreateSource);
+        match config.create_source(&id, false) {
+            Ok(path) => self.app_state = AppState::Msg(format!("Created {}", path.display())),
+            Err(e) => self.errors.add(Error::CannotCreateSource(e.to_string())),
+        }
     }
+    fn accept_test_output(&mut self, id: TaskID, index: usize, test: Test) {
+        self.errors.delete(ErrorKind::BugConfigEmpty);
+        let Some(config) = &mut self.config else {
+            return self.errors.add(Error::BugConfigEmpty);
+        };
+        config.update_test(&id, index, test);
 
-    fn handle_post_update(&mut self) {
+        // keep showing results of other tests after saving
+        let state = std::mem::take(&mut self.app_state);
+        self.save_config();
+        if let (AppState::Msg(_), AppState::ShowTestsResults(task_id, mut results, changes)) =
+            (&self.app_state, state)
+        {
+            for res in &mut results {
+                if let TestResult::Failed(f) = res {
+                    if f.index == index {
+                        *res = TestResult::Ok(f.cmd_output.time);
+                    }
+                }
+            }
+            self.app_state = AppState::ShowTestsResults(task_id, results, changes);
+        }
+    }
+
+    fn handle_post_update(&mut self, ctx: &egui::Context) {
         match &self.post_update {
             PostUpdate::SaveConfig => self.save_config(),
+            PostUpdate::MergeConfig => self.merge_config(),
+            PostUpdate::OverwriteConfig => self.overwrite_config(),
             PostUpdate::OpenConfigInEditor => self.open_config_in_editor(),
+            PostUpdate::OpenConfig(path) => {
+                self.config_path = Some(path.clone());
+                self.config = None;
+            }
+            PostUpdate::OpenSource(location) => self.open_source(&location.clone()),
             PostUpdate::CancelOperation => self.clear_app_state(),
-            PostUpdate::RunTests(id) => self.run_tests(id.clone()),
+            PostUpdate::RunTests(id) => self.run_tests(id.clone(), ctx),
+            PostUpdate::CancelTests => self.cancel_tests(),
+            PostUpdate::ShowHistory(id) => self.show_history(id.clone()),
+            PostUpdate::CreateSource(id) => self.create_source(id.clone()),
+            PostUpdate::AcceptTestOutput(id, index, test) => {
+                self.accept_test_output(id.clone(), *index, test.clone())
+            }
             PostUpdate::None => (),
         }
         self.post_update = Default::default();
@@ -0,0 +1,36 @@
+//! Opening source files at location of build diagnostics
+
+use std::{env::var, io, path::Path, process::Command};
+
+use lib::SourceLocation;
+
+/// Editors, which open `file:line:column` given with `-g` flag
+const GOTO_FLAG_EDITORS: &[&str] = &["code", "code-insiders", "codium", "cursor"];
+/// Editors, which open `file:line:column` given as is
+const GOTO_PATH_EDITORS: &[&str] = &["subl", "zed"];
+
+/// Open file in editor from `VISUAL` or `EDITOR` at location, if it supports this.
+/// Otherwise file is opened by system, at its beginning
+pub(crate) fn open_at(location: &SourceLocation) -> io::Result<()> {
+    let editor = var("VISUAL").or_else(|_| var("EDITOR")).unwrap_or_default();
+    let mut words = editor.split_whitespace();
+    let Some(program) = words.next() else {
+        return open::that_detached(&location.file);
+    };
+    let name = Path::new(program)
+        .file_stem()
+        .unwrap_or_default()
+        .to_string_lossy();
+    let goto = location.to_string();
+    let mut command = Command::new(program);
+    command.args(words);
+    if GOTO_FLAG_EDITORS.contains(&name.as_ref()) {
+        command.args(["-g", &goto]);
+    } else if GOTO_PATH_EDITORS.contains(&name.as_ref()) {
+        command.arg(&goto);
+    } else {
+        // terminal editors cannot be opened from GUI
+        return open::that_detached(&location.file);
+    }
+    command.spawn().map(|_| ())
+}
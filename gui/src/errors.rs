@@ -16,17 +16,29 @@ pub(crate) enum ErrorKind {
     CannotReadConfig,
     #[error("cannot parse config")]
     CannotParseConfig,
+    #[error("problems in config")]
+    ConfigDiagnostics,
     #[error("cannot save config")]
     CannotSaveConfig,
+    #[error("cannot merge config")]
+    CannotMergeConfig,
     #[error("cannot open config in editor")]
     CannotOpenConfigInEditor,
     #[error("{} does not exists", .0.display())]
     PathNotExists(PathBuf),
 
+    #[error("cannot create solution source")]
+    CannotCreateSource,
     #[error("cannot build task")]
     CannotBuildTask,
+    #[error("cannot open source")]
+    CannotOpenSource,
     #[error("error running test")]
     ErrorRunningTest,
+    #[error("cannot add test")]
+    InvalidTestInput,
+    #[error("cannot update history")]
+    CannotUpdateHistory,
 
     #[error("self.config is empty. {BUG_STR}")]
     BugConfigEmpty,
@@ -45,17 +57,29 @@ pub(crate) enum Error {
     CannotReadConfig(String),
     #[error("{0}")]
     CannotParseConfig(String, PathBuf),
+    #[error("\n{}", .0.join("\n"))]
+    ConfigDiagnostics(Vec<String>),
     #[error("{0}")]
     CannotSaveConfig(String),
     #[error("{0}")]
+    CannotMergeConfig(String),
+    #[error("{0}")]
     CannotOpenConfigInEditor(String),
     #[error("{0}")]
     PathNotExists(String, PathBuf),
 
+    #[error("{0}")]
+    CannotCreateSource(String),
     #[error("{0}")]
     CannotBuildTask(String),
     #[error("{0}")]
+    CannotOpenSource(String),
+    #[error("{0}")]
     CannotRunTests(String),
+    #[error("{0}")]
+    InvalidTestInput(String),
+    #[error("{0}")]
+    CannotUpdateHistory(String),
 
     // instead of unreachable!()
     #[error("")]
@@ -71,12 +95,18 @@ impl Error {
             Self::CannotSelectPathForSavingConfig => ErrorKind::CannotSelectPathForSavingConfig,
             Self::CannotReadConfig(_) => ErrorKind::CannotReadConfig,
             Self::CannotParseConfig(_, _) => ErrorKind::CannotParseConfig,
+            Self::ConfigDiagnostics(_) => ErrorKind::ConfigDiagnostics,
             Self::CannotSaveConfig(_) => ErrorKind::CannotSaveConfig,
+            Self::CannotMergeConfig(_) => ErrorKind::CannotMergeConfig,
             Self::CannotOpenConfigInEditor(_) => ErrorKind::CannotOpenConfigInEditor,
             Self::PathNotExists(_, path) => ErrorKind::PathNotExists(path.clone()),
 
+            Self::CannotCreateSource(_) => ErrorKind::CannotCreateSource,
             Self::CannotBuildTask(_) => ErrorKind::CannotBuildTask,
+            Self::CannotOpenSource(_) => ErrorKind::CannotOpenSource,
             Error::CannotRunTests(_) => ErrorKind::ErrorRunningTest,
+            Self::InvalidTestInput(_) => ErrorKind::InvalidTestInput,
+            Self::CannotUpdateHistory(_) => ErrorKind::CannotUpdateHistory,
 
             Self::BugConfigEmpty => ErrorKind::BugConfigEmpty,
             Self::BugConfigPathEmpty => ErrorKind::BugConfigPathEmpty,
@@ -9,8 +9,10 @@ use app::{App, CONFIG_PATH_STORAGE_KEY};
 use eframe::egui;
 
 mod app;
+mod editor;
 mod errors;
 mod widgets;
+mod worker;
 
 const GUI_SCALE: f32 = 1.5;
 
@@ -4,6 +4,8 @@ use eframe::egui::{Response, Ui, Widget};
 pub(crate) struct AddTaskState {
     pub(crate) id: String,
     pub(crate) name: String,
+    /// Create solution source from template
+    pub(crate) create_source: bool,
 }
 
 fn add_task_ui(ui: &mut Ui, state: &mut AddTaskState) -> Response {
@@ -18,6 +20,7 @@ fn add_task_ui(ui: &mut Ui, state: &mut AddTaskState) -> Response {
         ui.text_edit_singleline(&mut state.name)
             .labelled_by(name_label.id);
     });
+    ui.checkbox(&mut state.create_source, "Create solution from template");
     ui.button("Submit")
 }
 
@@ -1,6 +1,6 @@
 use eframe::egui::{Response, Ui, Widget};
 
-use lib::{TaskID, Test};
+use lib::{GeneratedTest, TaskID, Test};
 
 #[derive(Debug, Default)]
 pub(crate) struct EditTestsState {
@@ -9,6 +9,8 @@ pub(crate) struct EditTestsState {
     // TODO: move to trait as .response()?
     pub(crate) response: EditTestsResponse,
     pub(crate) tests: Vec<Test>,
+    /// Generated tests are listed after tests, but cannot be edited
+    pub(crate) generated: Vec<GeneratedTest>,
 }
 
 #[derive(Debug, Default)]
@@ -20,10 +22,11 @@ pub(crate) enum EditTestsResponse {
 }
 
 impl EditTestsState {
-    pub(crate) fn new(id: &TaskID, tests: &[Test]) -> Self {
+    pub(crate) fn new(id: &TaskID, tests: &[Test], generated: &[GeneratedTest]) -> Self {
         Self {
             id: id.clone(),
             tests: tests.into(),
+            generated: generated.into(),
             ..Default::default()
         }
     }
@@ -40,6 +43,10 @@ fn edit_tests_ui(ui: &mut Ui, state: &mut EditTestsState) -> Response {
             ui.label(format!("test {}", i + 1));
         });
     }
+    for (i, t) in state.generated.iter().enumerate() {
+        let n = state.tests.len() + i + 1;
+        ui.label(format!("test {n}: generated by `{}`", t.command(&state.id)));
+    }
 
     let mut cancel_editing = false;
     if let Some((i, t)) = &mut state.edited_test {
@@ -0,0 +1,124 @@
+//! Building and testing of task in background thread, so that window is not frozen
+
+use std::{
+    sync::mpsc::{channel, Receiver, TryRecvError},
+    thread,
+    time::{Duration, Instant},
+};
+
+use eframe::egui;
+use lib::{CancelToken, CommandOutput, Config, Error as LibError, TaskID, TestResult};
+
+#[derive(Debug)]
+enum Message {
+    Built(CommandOutput),
+    BuildError(LibError),
+    /// Build succeeded, tests are running
+    Testing,
+    Result(TestResult),
+    /// All tests were run, with these hashes of inputs
+    InputHashes(Vec<String>),
+}
+
+/// Event of running tests, received by [`TestsWorker::poll`]
+#[derive(Debug)]
+pub(crate) enum TestsEvent {
+    /// Output of build command, successful or not
+    Built(CommandOutput),
+    BuildError(LibError),
+    /// Worker finished: all tests were run, or build failed, or it was cancelled
+    Finished,
+}
+
+#[derive(Debug)]
+pub(crate) struct TestsWorker {
+    pub(crate) id: TaskID,
+    pub(crate) total: usize,
+    pub(crate) building: bool,
+    pub(crate) results: Vec<TestResult>,
+    /// Hashes of inputs of tests for history, computed here, as generators may be run
+    pub(crate) input_hashes: Vec<String>,
+    started: Instant,
+    cancel: CancelToken,
+    receiver: Receiver<Message>,
+}
+
+impl TestsWorker {
+    /// Build task and run its tests with copy of `config`
+    pub(crate) fn spawn(mut config: Config, id: TaskID, total: usize, ctx: egui::Context) -> Self {
+        let cancel = CancelToken::new();
+        config.set_cancel_token(cancel.clone());
+        let (sender, receiver) = channel();
+        let building = config.should_build(&id);
+        let task_id = id.clone();
+        thread::spawn(move || {
+            let send = |message| {
+                let sent = sender.send(message).is_ok();
+                ctx.request_repaint();
+                sent
+            };
+            if building {
+                match config.build(&task_id) {
+                    Ok(Some(output)) => {
+                        let success = output.success;
+                        send(Message::Built(output));
+                        if !success {
+                            return;
+                        }
+                    }
+                    Ok(None) => (),
+                    Err(e) => {
+                        send(Message::BuildError(e));
+                        return;
+                    }
+                }
+                send(Message::Testing);
+            }
+            for result in config.run_tests(&task_id) {
+                if !send(Message::Result(result)) {
+                    return;
+                }
+            }
+            send(Message::InputHashes(config.input_hashes(&task_id)));
+        });
+        Self {
+            id,
+            total,
+            building,
+            results: vec![],
+            input_hashes: vec![],
+            started: Instant::now(),
+            cancel,
+            receiver,
+        }
+    }
+    /// Kill running command and stop testing
+    pub(crate) fn cancel(&self) {
+        self.cancel.cancel()
+    }
+    pub(crate) fn is_cancelled(&self) -> bool {
+        self.cancel.is_cancelled()
+    }
+    pub(crate) fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+    /// Receive messages sent by worker since last poll, collecting test results
+    pub(crate) fn poll(&mut self) -> Vec<TestsEvent> {
+        let mut events = vec![];
+        loop {
+            match self.receiver.try_recv() {
+                Ok(Message::Built(output)) => events.push(TestsEvent::Built(output)),
+                Ok(Message::BuildError(e)) => events.push(TestsEvent::BuildError(e)),
+                Ok(Message::Testing) => self.building = false,
+                Ok(Message::Result(result)) => self.results.push(result),
+                Ok(Message::InputHashes(hashes)) => self.input_hashes = hashes,
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    events.push(TestsEvent::Finished);
+                    break;
+                }
+            }
+        }
+        events
+    }
+}
@@ -0,0 +1,260 @@
+//! Benchmarking of solutions, with baselines stored next to config
+
+use std::{
+    collections::BTreeMap,
+    fs::{read_to_string, write},
+    path::PathBuf,
+    time::Duration,
+};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{date::now, history::hash, Config, Error, FailedTest, Result, TaskID};
+
+/// Name of file with baselines, saved next to config
+pub const BENCH_FILE_NAME: &str = "cdf.bench.json";
+const DEFAULT_THRESHOLD: f64 = 10.0;
+const DEFAULT_TIME_LIMIT_WARNING: f64 = 80.0;
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize, JsonSchema)]
+/// Settings of `cdf bench`
+pub(crate) struct BenchSettings {
+    /// Slowdown in percent to warn about, default is 10
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) threshold: Option<f64>,
+    /// Percent of time limit to warn about, default is 80
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) time_limit_warning: Option<f64>,
+}
+
+/// Wall time of test over several runs, in microseconds
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TestTiming {
+    pub min_us: u64,
+    pub median_us: u64,
+    pub max_us: u64,
+    /// Median absolute deviation from median
+    pub mad_us: u64,
+    /// Hash of input of test, to compare timings of the same test
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_hash: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Baseline {
+    /// Seconds since 1970-01-01
+    pub time: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub solution_hash: Option<String>,
+    pub tests: Vec<TestTiming>,
+}
+
+/// Result of benchmarking test: timing, or run with wrong output
+#[derive(Debug)]
+pub enum BenchResult {
+    Ok(TestTiming),
+    Failed(FailedTest),
+    Err(Error),
+}
+
+impl TestTiming {
+    fn from_samples(samples: &mut [u64]) -> Self {
+        samples.sort_unstable();
+        let median_us = median(samples);
+        let mut deviations: Vec<u64> = samples.iter().map(|s| s.abs_diff(median_us)).collect();
+        deviations.sort_unstable();
+        Self {
+            min_us: samples.first().copied().unwrap_or_default(),
+            median_us,
+            max_us: samples.last().copied().unwrap_or_default(),
+            mad_us: median(&deviations),
+            input_hash: None,
+        }
+    }
+    pub fn median(&self) -> Duration {
+        Duration::from_micros(self.median_us)
+    }
+    /// Noise of measurements in percent of median
+    pub fn noise(&self) -> f64 {
+        percent(self.mad_us, self.median_us)
+    }
+    /// Change of median since `baseline` in percent
+    pub fn change(&self, baseline: &TestTiming) -> f64 {
+        percent(self.median_us, baseline.median_us) - 100.0
+    }
+    /// Median is slower than `baseline` by more than `threshold` percent and than noise of
+    /// both measurements
+    pub fn is_slower(&self, baseline: &TestTiming, threshold: f64) -> bool {
+        let noise = 2 * self.mad_us.max(baseline.mad_us);
+        self.change(baseline) > threshold && self.median_us > baseline.median_us + noise
+    }
+}
+
+impl Baseline {
+    /// Timing of the same test in baseline, found by hash of input, so that tests can be
+    /// added, removed or reordered
+    pub fn test(&self, timing: &TestTiming) -> Option<&TestTiming> {
+        let input_hash = timing.input_hash.as_ref()?;
+        self.tests
+            .iter()
+            .find(|t| t.input_hash.as_ref() == Some(input_hash))
+    }
+}
+
+fn median(sorted: &[u64]) -> u64 {
+    match sorted.len() {
+        0 => 0,
+        n if n % 2 == 1 => sorted[n / 2],
+        n => (sorted[n / 2 - 1] + sorted[n / 2]) / 2,
+    }
+}
+
+fn percent(value: u64, of: u64) -> f64 {
+    if of == 0 {
+        return 0.0;
+    }
+    value as f64 * 100.0 / of as f64
+}
+
+impl Config {
+    /// Run each test of task `repeat` times, stopping at first wrong output of test
+    pub fn bench<'s>(
+        &'s self,
+        id: &'s TaskID,
+        repeat: usize,
+    ) -> impl Iterator<Item = BenchResult> + 's {
+        let (tests, error) = match self.all_tests(id) {
+            Ok(tests) => (tests, None),
+            Err(e) => (vec![], Some(BenchResult::Err(e))),
+        };
+        let results = tests.into_iter().enumerate().map(move |(i, test)| {
+            let mut samples = vec![];
+            for _ in 0..repeat.max(1) {
+                let output = match self.run(id, test.input.clone()) {
+                    Ok(output) => output,
+                    Err(e) => return BenchResult::Err(e),
+                };
+                if !output.is_accepted(&test.expected, self.fails_on_runtime_error(id)) {
+                    let failed = FailedTest::new(i, test.input, test.expected, output);
+                    return BenchResult::Failed(failed);
+                }
+                samples.push(output.time.as_micros() as u64);
+            }
+            let mut timing = TestTiming::from_samples(&mut samples);
+            timing.input_hash = Some(hash(test.input.as_bytes()));
+            BenchResult::Ok(timing)
+        });
+        error.into_iter().chain(results)
+    }
+    /// Slowdown in percent to warn about
+    pub fn bench_threshold(&self) -> f64 {
+        self.settings
+            .bench
+            .as_ref()
+            .and_then(|b| b.threshold)
+            .unwrap_or(DEFAULT_THRESHOLD)
+    }
+    /// Time of test, after which it is reported as close to time limit of task
+    pub fn time_limit_warning(&self, id: &TaskID) -> Option<Duration> {
+        let percent = self
+            .settings
+            .bench
+            .as_ref()
+            .and_then(|b| b.time_limit_warning)
+            .unwrap_or(DEFAULT_TIME_LIMIT_WARNING);
+        self.time_limit(id).map(|l| l.mul_f64(percent / 100.0))
+    }
+    pub fn time_limit(&self, id: &TaskID) -> Option<Duration> {
+        self.tasks
+            .get(id)
+            .and_then(|t| t.time_limit)
+            .map(Duration::from_millis)
+    }
+    fn bench_path(&self) -> PathBuf {
+        self.dir
+            .as_ref()
+            .map_or(BENCH_FILE_NAME.into(), |d| d.join(BENCH_FILE_NAME))
+    }
+    fn baselines(&self) -> Result<BTreeMap<TaskID, Baseline>> {
+        let path = self.bench_path();
+        if !path.exists() {
+            return Ok(BTreeMap::new());
+        }
+        let content = read_to_string(path).map_err(Error::CannotReadBaselines)?;
+        serde_json::from_str(&content).map_err(|e| Error::CannotReadBaselines(e.into()))
+    }
+    /// Last saved baseline of task
+    pub fn baseline(&self, id: &TaskID) -> Result<Option<Baseline>> {
+        Ok(self.baselines()?.remove(id))
+    }
+    /// Solution of task was not changed since `baseline`, so changes of timings are noise
+    pub fn is_baseline_solution(&self, id: &TaskID, baseline: &Baseline) -> bool {
+        baseline.solution_hash.is_some() && baseline.solution_hash == self.solution_hash(id)
+    }
+    /// Save timings of tests as baseline of task, replacing previous one
+    pub fn save_baseline(&self, id: &TaskID, tests: Vec<TestTiming>) -> Result<()> {
+        let mut baselines = self.baselines()?;
+        let baseline = Baseline {
+            time: now(),
+            solution_hash: self.solution_hash(id),
+            tests,
+        };
+        baselines.insert(id.clone(), baseline);
+        let content = serde_json::to_string_pretty(&baselines)
+            .map_err(|e| Error::CannotSaveBaselines(e.into()))?;
+        write(self.bench_path(), content).map_err(Error::CannotSaveBaselines)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timing(median_us: u64, input: &str) -> TestTiming {
+        let mut timing = TestTiming::from_samples(&mut [median_us]);
+        timing.input_hash = Some(hash(input.as_bytes()));
+        timing
+    }
+
+    #[test]
+    fn from_samples() {
+        let timing = TestTiming::from_samples(&mut [30, 10, 20, 100]);
+        assert_eq!(
+            (timing.min_us, timing.median_us, timing.max_us),
+            (10, 25, 100)
+        );
+        // deviations are 5, 5, 15, 75
+        assert_eq!(timing.mad_us, 10);
+        let timing = TestTiming::from_samples(&mut [7]);
+        assert_eq!((timing.median_us, timing.mad_us), (7, 0));
+        let timing = TestTiming::from_samples(&mut []);
+        assert_eq!((timing.median_us, timing.noise()), (0, 0.0));
+    }
+
+    #[test]
+    fn is_slower() {
+        let baseline = timing(1000, "");
+        assert!(timing(1200, "").is_slower(&baseline, 10.0));
+        assert!(!timing(1050, "").is_slower(&baseline, 10.0));
+        let mut noisy = timing(1200, "");
+        noisy.mad_us = 150;
+        assert!(!noisy.is_slower(&baseline, 10.0));
+    }
+
+    #[test]
+    fn baseline_matches_tests_by_input() {
+        let baseline = Baseline {
+            time: 0,
+            solution_hash: None,
+            tests: vec![timing(100, "1"), timing(200, "2")],
+        };
+        // test `1` was removed
+        assert_eq!(baseline.test(&timing(0, "2")).unwrap().median_us, 200);
+        assert!(baseline.test(&timing(0, "3")).is_none());
+        // baseline saved before inputs were hashed
+        assert!(baseline
+            .test(&TestTiming::from_samples(&mut [200]))
+            .is_none());
+    }
+}
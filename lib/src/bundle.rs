@@ -0,0 +1,270 @@
+//! Bundling of solution with local headers into single file for submission
+
+use std::{
+    collections::HashSet,
+    fs::{create_dir_all, read_to_string, write},
+    path::{Path, PathBuf},
+};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{exec::CommandOutput, temp::TempDir, Config, Error, Result, TaskID, TestResult};
+
+/// Default path to bundled source, relative to config
+const DEFAULT_OUTPUT: &str = "{id}.bundle.cpp";
+/// Macro defined only for local builds
+const LOCAL_MACRO: &str = "LOCAL";
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize, JsonSchema)]
+/// Settings of `cdf bundle`
+pub(crate) struct BundleSettings {
+    /// Path to bundled source, relative to config. Default is `{id}.bundle.cpp`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) output: Option<String>,
+    /// Directories to search local headers in, besides directory of including file
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) include_dirs: Vec<PathBuf>,
+    /// Remove `#ifdef LOCAL` blocks
+    #[serde(default)]
+    pub(crate) strip_local: bool,
+}
+
+/// Result of building and testing bundled source apart from local headers
+#[derive(Debug)]
+pub struct BundleCheck {
+    /// Output of build command, if task is built
+    pub build: Option<CommandOutput>,
+    pub results: Vec<TestResult>,
+}
+
+impl BundleCheck {
+    pub fn is_ok(&self) -> bool {
+        self.build.as_ref().is_none_or(|b| b.success)
+            && self.results.iter().all(|r| matches!(r, TestResult::Ok(_)))
+    }
+}
+
+impl Config {
+    /// Expand local `#include "..."` in solution source, optionally removing `#ifdef LOCAL`
+    /// blocks, and save it to `output` from bundle settings. Returns path to bundled source
+    pub fn bundle(&self, id: &TaskID, strip_local: bool) -> Result<PathBuf> {
+        let settings = self.settings.bundle.as_ref();
+        let source = self.source_path(id)?;
+        let include_dirs: Vec<PathBuf> = settings
+            .map(|s| {
+                s.include_dirs
+                    .iter()
+                    .map(|d| self.config_relative(d))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let mut content = String::new();
+        expand(&source, &include_dirs, &mut HashSet::new(), &mut content)?;
+        if strip_local || settings.is_some_and(|s| s.strip_local) {
+            content = strip_ifdef(&content, LOCAL_MACRO);
+        }
+
+        let output = settings
+            .and_then(|s| s.output.as_deref())
+            .unwrap_or(DEFAULT_OUTPUT)
+            .replace("{id}", id);
+        let output = self.config_relative(Path::new(&output));
+        if let Some(dir) = output.parent() {
+            create_dir_all(dir).map_err(|e| Error::CannotWriteBundle(output.clone(), e))?;
+        }
+        write(&output, content).map_err(|e| Error::CannotWriteBundle(output.clone(), e))?;
+        Ok(output)
+    }
+    /// Build and test bundled source in temporary directory, where local headers are not
+    /// available
+    pub fn check_bundle(&self, id: &TaskID, bundle: &Path) -> Result<BundleCheck> {
+        let settings = self.build_settings(id)?;
+        let Some(source) = &settings.source else {
+            return Err(Error::SourceNotSet(self.task_language(id).into()));
+        };
+        let content =
+            read_to_string(bundle).map_err(|e| Error::CannotReadSource(bundle.into(), e))?;
+        let dir = TempDir::new("bundle")?;
+        let path = dir.path().join(source.replace("{id}", id));
+        if let Some(parent) = path.parent() {
+            create_dir_all(parent).map_err(|e| Error::CannotWriteBundle(path.clone(), e))?;
+        }
+        write(&path, content).map_err(|e| Error::CannotWriteBundle(path.clone(), e))?;
+
+        let cwd = Some(dir.path().to_path_buf());
+        let build = self.build_in(id, cwd.clone())?;
+        if build.as_ref().is_some_and(|b| !b.success) {
+            return Ok(BundleCheck {
+                build,
+                results: vec![],
+            });
+        }
+        let results = self.run_tests_in(id, cwd).collect();
+        Ok(BundleCheck { build, results })
+    }
+    /// Path relative to config directory
+    fn config_relative(&self, path: &Path) -> PathBuf {
+        self.dir
+            .as_ref()
+            .map_or(path.to_path_buf(), |d| d.join(path))
+    }
+}
+
+/// Append `path` with local headers expanded to `out`. Every header is included only once
+fn expand(
+    path: &Path,
+    include_dirs: &[PathBuf],
+    included: &mut HashSet<PathBuf>,
+    out: &mut String,
+) -> Result<()> {
+    let content = read_to_string(path).map_err(|e| Error::CannotReadSource(path.into(), e))?;
+    let dir = path.parent().unwrap_or(Path::new(""));
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if trimmed == "#pragma once" {
+            continue;
+        }
+        let header = trimmed
+            .strip_prefix("#include")
+            .map(str::trim)
+            .and_then(|s| s.strip_prefix('"'))
+            .and_then(|s| s.split_once('"'))
+            .map(|(h, _)| h);
+        let found = header.and_then(|h| {
+            std::iter::once(dir)
+                .chain(include_dirs.iter().map(PathBuf::as_path))
+                .map(|d| d.join(h))
+                .find(|p| p.is_file())
+        });
+        match found {
+            Some(header) => {
+                let canonical = header.canonicalize().unwrap_or(header.clone());
+                if included.insert(canonical) {
+                    expand(&header, include_dirs, included, out)?;
+                }
+            }
+            None => {
+                out.push_str(line);
+                out.push('\n');
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Remove code under `#ifdef macro` (and `#else` of `#ifndef macro`)
+fn strip_ifdef(content: &str, macro_name: &str) -> String {
+    // conditional blocks, `Some(active)` for blocks depending on macro
+    let mut blocks: Vec<Option<bool>> = vec![];
+    let mut out = String::new();
+    for line in content.lines() {
+        let trimmed = line.trim_start();
+        let directive = trimmed
+            .strip_prefix('#')
+            .map(|d| d.split_whitespace().collect::<Vec<_>>());
+        let visible = blocks.iter().all(|b| b.unwrap_or(true));
+        match directive.as_deref() {
+            Some(["ifdef", m, ..]) if *m == macro_name => blocks.push(Some(false)),
+            Some(["ifndef", m, ..]) if *m == macro_name => blocks.push(Some(true)),
+            Some([d, ..]) if d.starts_with("if") => {
+                blocks.push(None);
+                push_line(&mut out, line, visible);
+            }
+            Some(["else", ..]) => match blocks.last_mut() {
+                Some(Some(active)) => *active = !*active,
+                _ => push_line(&mut out, line, visible),
+            },
+            Some(["endif", ..]) => match blocks.pop() {
+                Some(Some(_)) => (),
+                _ => push_line(&mut out, line, visible),
+            },
+            _ => push_line(&mut out, line, visible),
+        }
+    }
+    out
+}
+
+fn push_line(out: &mut String, line: &str, visible: bool) {
+    if visible {
+        out.push_str(line);
+        out.push('\n');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strips_local_blocks() {
+        let content = "\
+#include <bits/stdc++.h>
+#ifdef LOCAL
+#include \"debug.h\"
+#else
+#define dbg(...)
+#endif
+#ifndef LOCAL
+#define NDEBUG
+#else
+#define TRACE
+#endif
+#if __cplusplus >= 201703L
+#ifdef LOCAL
+  #ifdef TRACE
+  trace();
+  #endif
+#endif
+int x;
+#endif
+int main() {}
+";
+        assert_eq!(
+            strip_ifdef(content, LOCAL_MACRO),
+            "\
+#include <bits/stdc++.h>
+#define dbg(...)
+#define NDEBUG
+#if __cplusplus >= 201703L
+int x;
+#endif
+int main() {}
+"
+        );
+    }
+
+    #[test]
+    fn expands_local_headers_once() {
+        let dir = TempDir::new("test").unwrap();
+        let path = |name: &str| dir.path().join(name);
+        create_dir_all(path("lib")).unwrap();
+        create_dir_all(path("include")).unwrap();
+        write(
+            path("a.cpp"),
+            "#include <vector>\n#include \"lib/x.h\"\n#include \"y.h\"\nint main() {}\n",
+        )
+        .unwrap();
+        write(path("lib/x.h"), "#pragma once\n#include \"z.h\"\nint x;\n").unwrap();
+        write(path("lib/z.h"), "#pragma once\nint z;\n").unwrap();
+        write(
+            path("include/y.h"),
+            "#pragma once\n#include \"../lib/z.h\"\nint y;\n",
+        )
+        .unwrap();
+
+        let mut out = String::new();
+        expand(
+            &path("a.cpp"),
+            &[path("include")],
+            &mut HashSet::new(),
+            &mut out,
+        )
+        .unwrap();
+        assert_eq!(
+            out,
+            "#include <vector>\nint z;\nint x;\nint y;\nint main() {}\n"
+        );
+    }
+}
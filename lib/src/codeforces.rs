@@ -0,0 +1,222 @@
+//! Parsing of saved Codeforces pages
+//!
+//! Contest dashboard (`/contest/1850`) has ids and names of problems, complete problemset
+//! (`/contest/1850/problems`) also has samples
+
+use crate::Test;
+
+const CODEFORCES_URL: &str = "https://codeforces.com";
+
+#[derive(Debug, Clone, Default)]
+pub struct ContestPage {
+    pub id: Option<String>,
+    pub name: Option<String>,
+    pub problems: Vec<Problem>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Problem {
+    /// Problem index, e.g. `a`
+    pub id: String,
+    pub name: String,
+    pub tests: Vec<Test>,
+}
+
+impl ContestPage {
+    pub fn parse(html: &str) -> Self {
+        let problems = if html.contains("class=\"problem-statement\"") {
+            parse_statements(html)
+        } else {
+            parse_dashboard(html)
+        };
+        Self {
+            id: contest_id(html),
+            name: contest_name(html),
+            problems,
+        }
+    }
+}
+
+/// URL of contest dashboard on Codeforces
+pub fn contest_url(id: &str) -> String {
+    format!("{CODEFORCES_URL}/contest/{id}")
+}
+
+/// Id from links like `/contest/1850/problem/A`
+fn contest_id(html: &str) -> Option<String> {
+    html.split("/contest/").skip(1).find_map(|s| {
+        let (id, rest) = s.split_once('/')?;
+        let is_id = !id.is_empty() && id.chars().all(|c| c.is_ascii_digit());
+        (is_id && rest.starts_with("problem")).then(|| id.to_string())
+    })
+}
+
+/// Name from title like `Dashboard - Codeforces Round 886 (Div. 4) - Codeforces`
+fn contest_name(html: &str) -> Option<String> {
+    let title = text(between(html, "<title>", "</title>")?);
+    let title = title.trim().trim_end_matches("- Codeforces").trim();
+    let name = title
+        .split_once(" - ")
+        .map_or(title, |(_, name)| name)
+        .trim();
+    (!name.is_empty() && name != "Problems").then(|| name.to_string())
+}
+
+/// Problems from links to them, first link is problem index and second is its name
+fn parse_dashboard(html: &str) -> Vec<Problem> {
+    let mut problems: Vec<Problem> = vec![];
+    for link in html.split("/problem/").skip(1) {
+        let Some((index, rest)) = link.split_once('"') else {
+            continue;
+        };
+        let Some(content) = rest
+            .split_once('>')
+            .and_then(|(_, r)| r.split_once("</a>"))
+            .map(|(c, _)| text(c).trim().to_string())
+        else {
+            continue;
+        };
+        let id = index.to_lowercase();
+        match problems.iter_mut().find(|p| p.id == id) {
+            Some(p) if p.name.is_empty() && !content.eq_ignore_ascii_case(index) => {
+                p.name = content
+            }
+            Some(_) => (),
+            None => problems.push(Problem {
+                id,
+                ..Default::default()
+            }),
+        }
+    }
+    problems
+}
+
+/// Problems with samples from statements
+fn parse_statements(html: &str) -> Vec<Problem> {
+    html.split("class=\"problem-statement\"")
+        .skip(1)
+        .filter_map(|statement| {
+            let title = text(between(statement, "class=\"title\">", "</div>")?);
+            let (id, name) = title.split_once('.')?;
+            let inputs = blocks(statement, "class=\"input\"");
+            let outputs = blocks(statement, "class=\"output\"");
+            let tests = inputs
+                .zip(outputs)
+                .map(|(input, expected)| Test::new(format!("{input}\n"), expected))
+                .collect();
+            Some(Problem {
+                id: id.trim().to_lowercase(),
+                name: name.trim().to_string(),
+                tests,
+            })
+        })
+        .collect()
+}
+
+/// Contents of `<pre>` after each `class` marker
+fn blocks<'a>(html: &'a str, class: &'a str) -> impl Iterator<Item = String> + 'a {
+    html.split(class).skip(1).filter_map(|s| {
+        let pre = between(s, "<pre", "</pre>")?;
+        let pre = pre.split_once('>').map_or("", |(_, p)| p);
+        // lines of samples are either separated with <br> or wrapped in <div>
+        let pre = if pre.contains("test-example-line") {
+            pre.replace('\n', "")
+        } else {
+            pre.to_string()
+        };
+        let pre = pre
+            .replace("<br />", "\n")
+            .replace("<br/>", "\n")
+            .replace("<br>", "\n")
+            .replace("</div>", "\n");
+        let lines: Vec<String> = text(&pre).lines().map(|l| l.trim().to_string()).collect();
+        Some(lines.join("\n").trim().to_string())
+    })
+}
+
+fn between<'a>(s: &'a str, start: &str, end: &str) -> Option<&'a str> {
+    let (_, rest) = s.split_once(start)?;
+    rest.split_once(end).map(|(s, _)| s)
+}
+
+/// Text without tags, with unescaped entities
+fn text(html: &str) -> String {
+    let mut s = String::new();
+    let mut in_tag = false;
+    for c in html.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            c if !in_tag => s.push(c),
+            _ => (),
+        }
+    }
+    s.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_dashboard() {
+        let html = r#"<html><head>
+<title>Dashboard - Codeforces Round 886 (Div. 4) - Codeforces</title></head>
+<table class="problems">
+<tr><td class="id"><a href="/contest/1850/problem/A">A</a></td>
+<td><a href="/contest/1850/problem/A">To My Critics</a></td></tr>
+<tr><td class="id"><a href="/contest/1850/problem/B">B</a></td>
+<td><a href="/contest/1850/problem/B">Ten Words of Wisdom</a></td></tr>
+</table></html>"#;
+        let page = ContestPage::parse(html);
+        assert_eq!(page.id.as_deref(), Some("1850"));
+        assert_eq!(page.name.as_deref(), Some("Codeforces Round 886 (Div. 4)"));
+        let problems: Vec<_> = page
+            .problems
+            .iter()
+            .map(|p| (p.id.as_str(), p.name.as_str(), p.tests.len()))
+            .collect();
+        assert_eq!(
+            problems,
+            [("a", "To My Critics", 0), ("b", "Ten Words of Wisdom", 0)]
+        );
+    }
+
+    #[test]
+    fn contest_url_points_to_dashboard() {
+        assert_eq!(contest_url("1850"), "https://codeforces.com/contest/1850");
+    }
+
+    #[test]
+    fn parses_statements_with_samples() {
+        let html = r#"<html><head><title>Problems - Codeforces</title></head>
+<a href="/contest/1850/problem/A">statement</a>
+<div class="problem-statement"><div class="header"><div class="title">A. To My Critics</div></div>
+<div class="sample-test"><div class="input"><div class="title">Input</div><pre>
+<div class="test-example-line test-example-line-even">2</div><div class="test-example-line test-example-line-odd">1 2 3</div><div class="test-example-line test-example-line-odd">4 &lt; 5</div></pre></div>
+<div class="output"><div class="title">Output</div><pre>YES
+NO
+</pre></div></div></div>
+<div class="problem-statement"><div class="header"><div class="title">B. Ten Words &amp; Wisdom</div></div>
+<div class="sample-test"><div class="input"><pre>1<br />5</pre></div>
+<div class="output"><pre>5</pre></div></div></div>
+</html>"#;
+        let page = ContestPage::parse(html);
+        assert_eq!(page.id.as_deref(), Some("1850"));
+        assert_eq!(page.name, None);
+        assert_eq!(page.problems.len(), 2);
+        let a = &page.problems[0];
+        assert_eq!((a.id.as_str(), a.name.as_str()), ("a", "To My Critics"));
+        assert_eq!(a.tests[0].input, "2\n1 2 3\n4 < 5\n");
+        assert_eq!(a.tests[0].expected, "YES\nNO");
+        let b = &page.problems[1];
+        assert_eq!(b.name, "Ten Words & Wisdom");
+        assert_eq!(b.tests[0].input, "1\n5\n");
+        assert_eq!(b.tests[0].expected, "5");
+    }
+}
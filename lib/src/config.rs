@@ -1,20 +1,39 @@
-use std::{collections::BTreeMap, fs::write as write_file, path::PathBuf};
+use std::{
+    collections::BTreeMap,
+    fs::{copy, read_to_string, rename, File},
+    io::Write,
+    path::{absolute, Path, PathBuf},
+    time::Duration,
+};
 
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use toml_edit::DocumentMut;
 
 use crate::{
-    exec::{exec, CommandOutput},
+    bench::BenchSettings,
+    bundle::BundleSettings,
+    contest::Contest,
+    crash::{parse_crash, CrashReport},
+    document::{format_document, merge_document},
+    exec::{exec, CancelToken, CommandOutput},
+    generated::GeneratedTest,
+    layers::{config_values, layer_tables, strip_inherited, ConfigValue, Layer},
+    sandbox::SandboxSettings,
+    submit::SubmitSettings,
     Error, Result, TaskID,
 };
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize, Serialize, JsonSchema)]
 pub struct Test {
+    /// Input to pass to program
     pub input: String,
+    /// Expected output from program
     pub expected: String,
 }
 
 impl Test {
-    fn new<S: Into<String>>(input: S, expected: S) -> Self {
+    pub fn new<S: Into<String>>(input: S, expected: S) -> Self {
         Self {
             input: input.into(),
             expected: expected.into(),
@@ -22,10 +41,33 @@ impl Test {
     }
 }
 
-#[derive(Debug, Default, Deserialize, Serialize)]
-struct Task {
-    name: String,
-    tests: Vec<Test>,
+#[derive(Clone, Debug, Default, Deserialize, Serialize, JsonSchema)]
+pub(crate) struct Task {
+    /// Name of task
+    pub(crate) name: String,
+    /// Language profile from `languages`, `settings.build` is used if not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) language: Option<String>,
+    /// Time limit in milliseconds, `cdf bench` warns when tests get close to it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) time_limit: Option<u64>,
+    /// Command printing random input of task, used by `cdf hack`. Placeholders: `{id}`,
+    /// `{seed}` - number of attempt, starting from 1
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) generator: Option<String>,
+    /// Command checking input of test, given to its stdin. It exits with error and message
+    /// for inputs violating constraints of task, like validators of testlib
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) validator: Option<String>,
+    /// Command of reference solution, printing expected output of generated tests for
+    /// input given to its stdin. Placeholder: `{id}`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) reference: Option<String>,
+    #[serde(default)]
+    pub(crate) tests: Vec<Test>,
+    /// Tests generated on demand, run after `tests`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) generated: Vec<GeneratedTest>,
 }
 
 #[derive(Debug)]
@@ -33,52 +75,160 @@ pub struct TaskInfo<'a> {
     pub id: &'a TaskID,
     pub name: &'a str,
     pub tests: &'a [Test],
+    pub generated: &'a [GeneratedTest],
+    /// Id of contest of task
+    pub contest: Option<&'a str>,
 }
 
 impl<'a> TaskInfo<'a> {
     pub fn new(id: &'a TaskID, name: &'a str, tests: &'a [Test]) -> Self {
-        Self { id, name, tests }
+        Self {
+            id,
+            name,
+            tests,
+            generated: &[],
+            contest: None,
+        }
     }
     pub fn format(&self) -> String {
-        format!(
+        let mut s = format!(
             "{} - {}, {} tests",
             self.id.to_uppercase(),
             self.name,
             self.tests.len()
-        )
+        );
+        if !self.generated.is_empty() {
+            s += &format!(", {} generated", self.generated.len());
+        }
+        s
     }
 }
 
-#[derive(Debug, Default, Deserialize, Serialize)]
-struct Settings {
-    build: BuildSettings,
+#[derive(Clone, Debug, Default, Deserialize, Serialize, JsonSchema)]
+pub(crate) struct Settings {
+    /// Build settings for tasks without language
+    pub(crate) build: BuildSettings,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) bundle: Option<BundleSettings>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) submit: Option<SubmitSettings>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) bench: Option<BenchSettings>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) sandbox: Option<SandboxSettings>,
 }
 
-#[derive(Debug, Default, Deserialize, Serialize)]
+#[derive(Clone, Debug, Default, Deserialize, Serialize, JsonSchema)]
 /// Available placeholders:
 /// - `{id}` - task id
-struct BuildSettings {
+pub(crate) struct BuildSettings {
     /// Build command (optional)
-    build: Option<String>,
+    pub(crate) build: Option<String>,
     /// Run command
-    run: String,
+    pub(crate) run: String,
     /// Working directory for executing commands, can be absolute or relative
-    cwd: Option<PathBuf>,
+    pub(crate) cwd: Option<PathBuf>,
+    /// Path to solution source, relative to `cwd`. Used by `cdf new` to create it from template
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) source: Option<String>,
+    /// Codeforces language id (`programTypeId`), used by `cdf submit`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) language_id: Option<u32>,
+    /// Fail build when compiler reports warnings
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) warnings_as_errors: Option<bool>,
+    /// Fail tests with correct output, when program exits with error or reports a crash
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) fail_on_runtime_error: Option<bool>,
+    /// Debugger command for `cdf debug`, placeholder `{input}` is path to file with input of
+    /// test, e.g. `gdb -ex "run < {input}" ./{id}.debug`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) debugger: Option<String>,
+    /// Named variants of commands, e.g. `debug`, selected with `cdf test --profile`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub(crate) profiles: BTreeMap<String, BuildProfile>,
 }
 
-#[derive(Debug, Default, Deserialize, Serialize)]
+#[derive(Clone, Debug, Default, Deserialize, Serialize, JsonSchema)]
+/// Commands replacing ones of build settings, when profile is selected
+pub(crate) struct BuildProfile {
+    /// Build command, `build` of build settings is used if not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) build: Option<String>,
+    /// Run command, `run` of build settings is used if not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) run: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize, JsonSchema)]
+/// Config of codeforces-tester
 pub struct Config {
     #[serde(default)]
-    settings: Settings,
+    pub(crate) settings: Settings,
+    /// Build settings for tasks with specified language
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub(crate) languages: BTreeMap<String, BuildSettings>,
+    /// Paths to solution templates by language, `default` is used for tasks without language.
+    /// Placeholders `{id}`, `{name}`, `{contest}` and `{date}` in templates are filled by
+    /// `cdf new`
     #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
-    tasks: BTreeMap<TaskID, Task>,
+    pub(crate) templates: BTreeMap<String, PathBuf>,
+    /// Contest of tasks from this config
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) contest: Option<Contest>,
+    /// Contests by id, tasks of contest have ids like `1850/a`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub(crate) contests: BTreeMap<String, Contest>,
+    /// Tasks by id, which is used in `cdf test [id]`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub(crate) tasks: BTreeMap<TaskID, Task>,
+    /// Document parsed from config file, used to preserve comments and formatting on saving
+    #[serde(skip)]
+    doc: DocumentMut,
+    /// Content of config file when it was loaded or saved, used to detect changes made by
+    /// other programs
+    #[serde(skip)]
+    pub(crate) source: Option<String>,
+    /// Directory of config file, commands are executed relative to it
+    #[serde(skip)]
+    pub(crate) dir: Option<PathBuf>,
+    /// User-level config, merged under this config
+    #[serde(skip)]
+    user: Option<Layer>,
+    /// Token to kill commands executed for this config
+    #[serde(skip)]
+    cancel: CancelToken,
+    /// Build profile, which commands are used instead of build settings
+    #[serde(skip)]
+    profile: Option<String>,
+    /// Run solutions in sandbox, regardless of settings
+    #[serde(skip)]
+    pub(crate) sandbox: bool,
 }
 
+/// Placeholders available in commands
+pub(crate) const PLACEHOLDERS: &[&str] = &["id"];
+/// Placeholders available in debugger command
+pub(crate) const DEBUGGER_PLACEHOLDERS: &[&str] = &["id", "input"];
+/// Placeholders available in generator command
+pub(crate) const GENERATOR_PLACEHOLDERS: &[&str] = &["id", "seed"];
+
+/// Name of config file, searched in current and parent directories
+pub const CONFIG_FILE_NAME: &str = "cdf.toml";
+/// Name of JSON Schema file, saved next to config
+pub const SCHEMA_FILE_NAME: &str = "cdf.schema.json";
+/// Comment for editors (e.g. Taplo) to use JSON Schema for completion and validation
+const SCHEMA_DIRECTIVE: &str = "#:schema ./cdf.schema.json";
+
 impl TryFrom<&str> for Config {
     type Error = toml::de::Error;
 
     fn try_from(value: &str) -> Result<Config, Self::Error> {
-        toml::from_str(value)
+        let mut config: Config = toml::from_str(value)?;
+        // already parsed by toml, so should not fail
+        config.doc = value.parse().unwrap_or_default();
+        config.source = Some(value.to_string());
+        Ok(config)
     }
 }
 
@@ -87,64 +237,206 @@ impl Config {
         let Some(task) = self.tasks.get(id) else {
             return Err(crate::Error::TaskNotFound(id.clone()));
         };
-        if task.tests.is_empty() {
+        if task.tests.is_empty() && task.generated.is_empty() {
             return Err(Error::TaskHasNoTests(id.clone()));
         }
         Ok(())
     }
-    pub fn should_build(&self) -> bool {
-        self.settings.build.build.is_some()
+    pub fn should_build(&self, id: &TaskID) -> bool {
+        self.build_settings(id)
+            .and_then(|s| self.commands(s))
+            .is_ok_and(|(build, _)| build.is_some())
+    }
+    /// Read and parse config file, merged over user config. Commands will be executed
+    /// relative to config directory
+    pub fn load(path: &Path) -> Result<Self> {
+        let s = read_to_string(path).map_err(Error::CannotReadConfig)?;
+        let mut config = match Layer::load_user()? {
+            Some(user) => {
+                let project = toml::from_str(&s).map_err(Error::CannotParseConfig)?;
+                let mut config = Self::from_layers(project, user)?;
+                config.doc = s.parse()?;
+                config.source = Some(s);
+                config
+            }
+            None => Self::try_from(s.as_str()).map_err(Error::CannotParseConfig)?,
+        };
+        let path = absolute(path).map_err(Error::CannotGetCwd)?;
+        config.dir = path.parent().map(Into::into);
+        Ok(config)
+    }
+    fn from_layers(project: toml::Table, user: Layer) -> Result<Self> {
+        let merged = layer_tables(&user.table, project);
+        let mut config: Config = toml::Value::Table(merged)
+            .try_into()
+            .map_err(Error::CannotParseConfig)?;
+        config.user = Some(user);
+        Ok(config)
+    }
+    /// Values of config merged with user config, with their origin. Tasks are not included
+    pub fn values(&self) -> Result<Vec<ConfigValue>> {
+        let toml::Value::Table(effective) = toml::Value::try_from(self)? else {
+            return Ok(vec![]);
+        };
+        Ok(config_values(
+            &effective,
+            self.user.as_ref(),
+            &self.project_table(),
+        ))
+    }
+    /// Path to user config, if it was loaded
+    pub fn user_config_path(&self) -> Option<&Path> {
+        self.user.as_ref().map(|u| u.path.as_path())
+    }
+    /// Values written in config file
+    fn project_table(&self) -> toml::Table {
+        toml::from_str(&self.doc.to_string()).unwrap_or_default()
+    }
+    /// Config without values inherited from user config
+    fn to_project_value(&self) -> Result<toml::Value> {
+        let mut value = toml::Value::try_from(self)?;
+        if let (Some(user), toml::Value::Table(table)) = (&self.user, &mut value) {
+            strip_inherited(table, &user.table, &self.project_table());
+        }
+        Ok(value)
+    }
+    pub(crate) fn build_settings(&self, id: &TaskID) -> Result<&BuildSettings> {
+        match self.tasks.get(id).and_then(|t| t.language.as_ref()) {
+            Some(lang) => self
+                .languages
+                .get(lang)
+                .ok_or_else(|| Error::LanguageNotFound(lang.clone())),
+            None => Ok(&self.settings.build),
+        }
+    }
+    /// Find config file in `dir` or any of its parents
+    pub fn find(dir: &Path) -> Option<PathBuf> {
+        dir.ancestors()
+            .map(|d| d.join(CONFIG_FILE_NAME))
+            .find(|p| p.is_file())
     }
     pub fn build(&self, id: &TaskID) -> Result<Option<CommandOutput>> {
-        // None because we running from current terminal directory + cwd from config
-        self.build_from_dir(id, &None)
+        // when dir is None, running from current terminal directory + cwd from config
+        self.build_from_dir(id, &self.dir)
     }
     pub fn run_tests<'s>(&'s self, id: &'s TaskID) -> impl IntoIterator<Item = TestResult> + 's {
-        self.run_tests_from_dir(id, &None)
+        self.run_tests_from_dir(id, &self.dir)
+    }
+    pub fn run(&self, id: &TaskID, input: String) -> Result<CommandOutput> {
+        self.run_from_dir(id, input, &self.dir)
     }
     pub fn build_from_dir(
         &self,
         id: &TaskID,
         config_dir: &Option<PathBuf>,
     ) -> Result<Option<CommandOutput>> {
-        let cwd = self.prepare_from_dir(config_dir);
-        if let Some(build) = &self.settings.build.build {
-            let out = exec(build.replace("{id}", id), None, cwd)?;
-            return Ok(Some(out));
-        }
-        Ok(None)
+        let settings = self.build_settings(id)?;
+        self.build_in(id, self.prepare_from_dir(settings, config_dir))
+    }
+    /// Run program with custom input, without comparing output to anything
+    pub fn run_from_dir(
+        &self,
+        id: &TaskID,
+        input: String,
+        dir: &Option<PathBuf>,
+    ) -> Result<CommandOutput> {
+        let settings = self.build_settings(id)?;
+        self.run_in(id, input, self.prepare_from_dir(settings, dir))
     }
     pub fn run_tests_from_dir<'s>(
         &'s self,
         id: &'s TaskID,
         dir: &'s Option<PathBuf>,
     ) -> impl IntoIterator<Item = TestResult> + 's {
-        let tests = self
-            .tasks
-            .get(id)
-            .map(|t| t.tests.clone())
-            .unwrap_or_default();
-        let cwd = self.prepare_from_dir(dir);
-        tests.into_iter().enumerate().map(move |(i, test)| {
-            let output = exec(
-                self.settings.build.run.replace("{id}", id),
-                Some(test.input),
-                cwd.clone(),
-            );
-            let output = match output {
+        let cwd = self
+            .build_settings(id)
+            .ok()
+            .and_then(|settings| self.prepare_from_dir(settings, dir));
+        self.run_tests_in(id, cwd)
+    }
+    /// Build task with working directory `cwd`
+    pub(crate) fn build_in(
+        &self,
+        id: &TaskID,
+        cwd: Option<PathBuf>,
+    ) -> Result<Option<CommandOutput>> {
+        let settings = self.build_settings(id)?;
+        if let (Some(build), _) = self.commands(settings)? {
+            let mut out = exec(build.replace("{id}", id), None, cwd, &self.cancel, None)?;
+            if settings.warnings_as_errors == Some(true) && out.warnings() > 0 {
+                out.success = false;
+            }
+            return Ok(Some(out));
+        }
+        Ok(None)
+    }
+    /// Run program with working directory `cwd`
+    pub(crate) fn run_in(
+        &self,
+        id: &TaskID,
+        input: String,
+        cwd: Option<PathBuf>,
+    ) -> Result<CommandOutput> {
+        let (_, run) = self.commands(self.build_settings(id)?)?;
+        let sandbox = self.sandbox();
+        exec(
+            run.replace("{id}", id),
+            Some(input),
+            cwd,
+            &self.cancel,
+            sandbox.as_ref(),
+        )
+    }
+    /// Runtime error fails test even with correct output, if enabled in build settings
+    pub(crate) fn fails_on_runtime_error(&self, id: &TaskID) -> bool {
+        self.build_settings(id)
+            .is_ok_and(|s| s.fail_on_runtime_error == Some(true))
+    }
+    /// Run tests of task with working directory `cwd`
+    pub(crate) fn run_tests_in<'s>(
+        &'s self,
+        id: &'s TaskID,
+        cwd: Option<PathBuf>,
+    ) -> impl Iterator<Item = TestResult> + 's {
+        let literal = self.tasks.get(id).map_or(0, |t| t.tests.len());
+        let strict = self.fails_on_runtime_error(id);
+        // error of generating tests is the only result
+        let (tests, error) = match self.all_tests(id) {
+            Ok(tests) => (tests, None),
+            Err(Error::Cancelled) => (vec![], Some(TestResult::Cancelled)),
+            Err(e) => (vec![], Some(TestResult::Err(e))),
+        };
+        let mut cancelled = false;
+        // stop after first cancelled test
+        let results = tests.into_iter().enumerate().map_while(move |(i, test)| {
+            if cancelled {
+                return None;
+            }
+            let output = match self.run_in(id, test.input.clone(), cwd.clone()) {
                 Ok(c) => c,
-                Err(e) => return TestResult::Err(e),
+                Err(Error::Cancelled) => {
+                    cancelled = true;
+                    return Some(TestResult::Cancelled);
+                }
+                Err(e) => return Some(TestResult::Err(e)),
             };
-            if output.stdout.trim() != test.expected.trim() {
-                TestResult::Failed(FailedTest::new(i, test.expected, output))
+            if !output.is_accepted(&test.expected, strict) {
+                let mut failed = FailedTest::new(i, test.input, test.expected, output);
+                failed.generated = i >= literal;
+                Some(TestResult::Failed(failed))
             } else {
-                TestResult::Ok
+                Some(TestResult::Ok(output.time))
             }
-        })
+        });
+        error.into_iter().chain(results)
     }
-    /// Append `settings.build.cwd` to provided `dir`
-    fn prepare_from_dir(&self, dir: &Option<PathBuf>) -> Option<PathBuf> {
-        let cwd = self.settings.build.cwd.clone();
+    /// Append `cwd` from build settings (or `settings.build.cwd` if not set) to provided `dir`
+    pub(crate) fn prepare_from_dir(
+        &self,
+        settings: &BuildSettings,
+        dir: &Option<PathBuf>,
+    ) -> Option<PathBuf> {
+        let cwd = settings.cwd.clone().or(self.settings.build.cwd.clone());
         match dir {
             Some(dir) => match cwd {
                 Some(cwd) => Some(dir.join(cwd)),
@@ -153,6 +445,36 @@ impl Config {
             None => cwd,
         }
     }
+    /// Build and run commands of selected profile
+    fn commands<'s>(&self, settings: &'s BuildSettings) -> Result<(Option<&'s str>, &'s str)> {
+        let build = settings.build.as_deref();
+        let Some(name) = &self.profile else {
+            return Ok((build, &settings.run));
+        };
+        let profile = settings
+            .profiles
+            .get(name)
+            .ok_or_else(|| Error::ProfileNotFound(name.clone()))?;
+        Ok((
+            profile.build.as_deref().or(build),
+            profile.run.as_deref().unwrap_or(&settings.run),
+        ))
+    }
+    /// Use commands of build profile instead of build settings, `None` to use build settings
+    pub fn set_profile(&mut self, profile: Option<String>) {
+        self.profile = profile
+    }
+    pub fn has_profile(&self, id: &TaskID, profile: &str) -> bool {
+        self.build_settings(id)
+            .is_ok_and(|s| s.profiles.contains_key(profile))
+    }
+    /// Use `token` to kill commands executed for this config, e.g. from other thread
+    pub fn set_cancel_token(&mut self, token: CancelToken) {
+        self.cancel = token
+    }
+    pub fn cancel_token(&self) -> &CancelToken {
+        &self.cancel
+    }
     pub fn get_task_name(&self, id: &TaskID) -> Option<String> {
         self.tasks.get(id).map(|t| t.name.clone())
     }
@@ -183,45 +505,245 @@ impl Config {
             .get_mut(index)
             .map(|t| *t = test);
     }
-    pub fn save_sample_to(path: &PathBuf) -> Result<()> {
+    /// Minimal config, with reference to JSON Schema for editors
+    pub fn minimal() -> Self {
+        let mut config = Self::default();
+        config
+            .doc
+            .decor_mut()
+            .set_prefix(format!("{SCHEMA_DIRECTIVE}\n"));
+        config
+    }
+    pub fn save_sample_to(path: &Path) -> Result<()> {
         let s = include_str!("../../docs/cdf.toml");
-        save_config_to(s, path)
+        save_config_to(&format!("{SCHEMA_DIRECTIVE}\n{s}"), path)
+    }
+    /// JSON Schema of config
+    pub fn schema() -> String {
+        let schema = schemars::schema_for!(Config);
+        serde_json::to_string_pretty(&schema).expect("schema should be serializable")
+    }
+    /// Save JSON Schema next to config
+    pub fn save_schema_near(config_path: &Path) -> Result<()> {
+        let path = config_path.with_file_name(SCHEMA_FILE_NAME);
+        std::fs::write(path, Self::schema()).map_err(Error::CannotSaveConfig)
+    }
+    /// Save config, keeping comments and formatting of unchanged values
+    ///
+    /// Returns [`Error::ConfigChangedOnDisk`] if file was changed since config was loaded
+    pub fn save_config_to(&mut self, path: &Path) -> Result<()> {
+        if self.is_changed_on_disk(path) {
+            return Err(Error::ConfigChangedOnDisk(path.to_path_buf()));
+        }
+        self.overwrite_config_to(path)
+    }
+    /// Save config, ignoring changes made to file since config was loaded
+    pub fn overwrite_config_to(&mut self, path: &Path) -> Result<()> {
+        let new: DocumentMut = toml::to_string_pretty(&self.to_project_value()?)?.parse()?;
+        merge_document(&mut self.doc, &new);
+        let content = self.doc.to_string().trim_start().to_string();
+        save_config_to(&content, path)?;
+        self.source = Some(content);
+        Ok(())
     }
-    pub fn save_config_to(&self, path: &PathBuf) -> Result<()> {
-        let content = toml::to_string_pretty(self)?;
-        save_config_to(&content, path)
+    /// Check if file content differs from content when config was loaded or saved
+    pub fn is_changed_on_disk(&self, path: &Path) -> bool {
+        match &self.source {
+            Some(source) => read_to_string(path).is_ok_and(|s| &s != source),
+            None => false,
+        }
+    }
+    /// Apply changes made to config since it was loaded on top of the file content on disk.
+    /// On conflicting changes of the same value, value from this config is used
+    pub fn merge_from_disk(&mut self, path: &Path) -> Result<()> {
+        let theirs = read_to_string(path).map_err(Error::CannotReadConfig)?;
+        let parse = |s: &str| toml::from_str::<toml::Value>(s).map_err(Error::CannotParseConfig);
+        let base = parse(self.source.as_deref().unwrap_or_default())?;
+        let ours = self.to_project_value()?;
+        let merged = merge_values(Some(&base), Some(&ours), Some(&parse(&theirs)?))
+            .unwrap_or_else(|| toml::Table::new().into());
+
+        let mut config: Config = match (self.user.take(), merged) {
+            (Some(user), toml::Value::Table(merged)) => Self::from_layers(merged, user)?,
+            (_, merged) => merged.try_into().map_err(Error::CannotParseConfig)?,
+        };
+        config.doc = theirs.parse()?;
+        config.source = Some(theirs);
+        config.dir = self.dir.take();
+        config.cancel = self.cancel.clone();
+        config.profile = self.profile.take();
+        config.sandbox = self.sandbox;
+        *self = config;
+        Ok(())
+    }
+    /// Normalize whitespace in config, keeping comments
+    pub fn format(&mut self) {
+        format_document(&mut self.doc)
     }
     pub fn tasks(&self) -> impl Iterator<Item = TaskInfo<'_>> + '_ {
-        self.tasks
-            .iter()
-            .map(|(k, v)| TaskInfo::new(k, &v.name, &v.tests))
+        self.tasks.iter().map(|(k, v)| TaskInfo {
+            contest: self.task_contest(k),
+            generated: &v.generated,
+            ..TaskInfo::new(k, &v.name, &v.tests)
+        })
     }
 }
 
-fn save_config_to(s: &str, path: &PathBuf) -> Result<()> {
-    write_file(path, s.as_bytes()).map_err(Error::CannotSaveConfig)
+/// Write config atomically through temporary file, keeping previous version in `.bak` file
+fn save_config_to(s: &str, path: &Path) -> Result<()> {
+    let with_suffix = |suffix: &str| {
+        let mut name = path.file_name().unwrap_or_default().to_os_string();
+        name.push(suffix);
+        path.with_file_name(name)
+    };
+    let tmp_path = with_suffix(".tmp");
+    let mut file = File::create(&tmp_path).map_err(Error::CannotSaveConfig)?;
+    file.write_all(s.as_bytes())
+        .and_then(|_| file.sync_all())
+        .map_err(Error::CannotSaveConfig)?;
+    if path.exists() {
+        copy(path, with_suffix(".bak")).map_err(Error::CannotSaveConfig)?;
+    }
+    rename(&tmp_path, path).map_err(Error::CannotSaveConfig)
+}
+
+/// Three-way merge of values: changes from both `ours` and `theirs` relative to `base` are
+/// kept, `ours` wins on conflicts. `None` means that value is missing
+fn merge_values(
+    base: Option<&toml::Value>,
+    ours: Option<&toml::Value>,
+    theirs: Option<&toml::Value>,
+) -> Option<toml::Value> {
+    use toml::Value;
+
+    if ours == base {
+        return theirs.cloned();
+    }
+    if theirs == base || ours == theirs {
+        return ours.cloned();
+    }
+    match (base, ours, theirs) {
+        (base, Some(Value::Table(ours)), Some(Value::Table(theirs))) => {
+            let base = base.and_then(Value::as_table);
+            let mut keys: Vec<&String> = theirs.keys().collect();
+            keys.extend(ours.keys().filter(|k| !theirs.contains_key(*k)));
+            let table = keys
+                .into_iter()
+                .filter_map(|k| {
+                    let base = base.and_then(|b| b.get(k));
+                    merge_values(base, ours.get(k), theirs.get(k)).map(|v| (k.clone(), v))
+                })
+                .collect();
+            Some(Value::Table(table))
+        }
+        (base, Some(Value::Array(ours)), Some(Value::Array(theirs))) => {
+            let base = base
+                .and_then(Value::as_array)
+                .map_or(&[][..], Vec::as_slice);
+            // merge common elements one by one, then append new elements from both sides
+            let mut array: Vec<Value> = base
+                .iter()
+                .enumerate()
+                .filter_map(|(i, b)| merge_values(Some(b), ours.get(i), theirs.get(i)))
+                .collect();
+            array.extend(theirs.iter().skip(base.len()).cloned());
+            array.extend(ours.iter().skip(base.len()).cloned());
+            Some(Value::Array(array))
+        }
+        (_, ours, _) => ours.cloned(),
+    }
 }
 
 #[derive(Debug)]
 pub enum TestResult {
-    Ok,
+    /// Output matches, with time of running program
+    Ok(Duration),
     Failed(FailedTest),
     Err(Error),
+    /// Testing was cancelled with [`CancelToken`], remaining tests are not run
+    Cancelled,
 }
 
 #[derive(Debug)]
 pub struct FailedTest {
     pub index: usize,
+    pub input: String,
     pub expected: String,
     pub cmd_output: CommandOutput,
+    /// Report of sanitizer, panic or assertion found in stderr
+    pub crash: Option<CrashReport>,
+    /// Test is generated, so its expected output cannot be replaced
+    pub generated: bool,
 }
 
 impl FailedTest {
-    fn new<S: Into<String>>(index: usize, expected: S, cmd_output: CommandOutput) -> Self {
+    pub(crate) fn new<S: Into<String>>(
+        index: usize,
+        input: S,
+        expected: S,
+        cmd_output: CommandOutput,
+    ) -> Self {
         Self {
             index,
+            input: input.into(),
             expected: expected.into(),
+            crash: parse_crash(&cmd_output.stderr),
             cmd_output,
+            generated: false,
         }
     }
+    /// Program exited with error or reported a crash, e.g. memory leak, even if its output
+    /// is correct
+    pub fn is_runtime_error(&self) -> bool {
+        !self.cmd_output.success || self.crash.is_some()
+    }
+    /// Test with actual output as expected, for accepting output of trusted solution
+    pub fn accepted(&self) -> Test {
+        Test::new(self.input.as_str(), self.cmd_output.stdout.trim())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn merge(base: &str, ours: &str, theirs: &str) -> toml::Value {
+        let parse = |s: &str| s.parse::<toml::Table>().map(toml::Value::Table).unwrap();
+        merge_values(Some(&parse(base)), Some(&parse(ours)), Some(&parse(theirs))).unwrap()
+    }
+
+    #[test]
+    fn merge_values_keeps_changes_of_both_sides() {
+        let base = "a = 1\nb = 1\nc = 1";
+        let merged = merge(base, "a = 2\nb = 1\nc = 1", "a = 1\nb = 3");
+        assert_eq!(merged, merge("", "", "a = 2\nb = 3"));
+    }
+
+    #[test]
+    fn merge_values_prefers_ours_on_conflict() {
+        let merged = merge("a = 1", "a = 2", "a = 3");
+        assert_eq!(merged["a"].as_integer(), Some(2));
+    }
+
+    #[test]
+    fn merge_values_appends_new_elements_of_arrays() {
+        let merged = merge("t = [1, 2]", "t = [1, 5, 3]", "t = [4, 2, 6]");
+        let t: Vec<_> = merged["t"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter_map(toml::Value::as_integer)
+            .collect();
+        assert_eq!(t, [4, 5, 6, 3]);
+    }
+
+    #[test]
+    fn merge_values_merges_nested_tables() {
+        let base = "[tasks.a]\nname = 'A'";
+        let ours = "[tasks.a]\nname = 'Echo'";
+        let theirs = "[tasks.a]\nname = 'A'\n[tasks.b]\nname = 'Sum'";
+        let merged = merge(base, ours, theirs);
+        assert_eq!(merged["tasks"]["a"]["name"].as_str(), Some("Echo"));
+        assert_eq!(merged["tasks"]["b"]["name"].as_str(), Some("Sum"));
+    }
 }
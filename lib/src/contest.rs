@@ -0,0 +1,293 @@
+//! Contests, grouping tasks
+//!
+//! Tasks of one contest can be described with `[contest]` section, tasks of several contests
+//! can share one config with ids like `1850/a` and `[contests.1850]` sections, or contests can
+//! be stored in directories of a workspace, each with its own config
+
+use std::path::PathBuf;
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{codeforces::Problem, Config, Error, Result, TaskID, CONFIG_FILE_NAME};
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize, JsonSchema)]
+pub struct Contest {
+    /// Contest id, e.g. `1850`. Key is used for contests from `contests`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// Date of contest, e.g. `2023-07-21`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub date: Option<String>,
+    /// Directory of contest with its own config, relative to this config
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dir: Option<PathBuf>,
+}
+
+impl Contest {
+    pub fn format(&self, id: &str) -> String {
+        let mut s = format!("Contest {id}");
+        if let Some(name) = &self.name {
+            s += &format!(" - {name}");
+        }
+        if let Some(date) = &self.date {
+            s += &format!(" ({date})");
+        }
+        s
+    }
+}
+
+/// Problem ids from list like `a-h` or `a,b,c1,c2`, returns `None` on invalid range
+pub fn parse_problem_ids(list: &str) -> Option<Vec<String>> {
+    let mut ids = vec![];
+    for part in list.split(',').map(|p| p.trim().to_lowercase()) {
+        match part.split_once('-') {
+            Some((from, to)) => {
+                let (&[from], &[to]) = (from.as_bytes(), to.as_bytes()) else {
+                    return None;
+                };
+                if !from.is_ascii_lowercase() || !to.is_ascii_lowercase() || from > to {
+                    return None;
+                }
+                ids.extend((from..=to).map(|c| char::from(c).to_string()));
+            }
+            None if !part.is_empty() => ids.push(part),
+            None => (),
+        }
+    }
+    Some(ids)
+}
+
+/// Config where task is stored
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TaskLocation {
+    /// Task from this config
+    Local(TaskID),
+    /// Task from config of contest directory
+    Workspace(PathBuf, TaskID),
+}
+
+impl Config {
+    /// Resolve task address, which is task id (`a`, `1850/a`) or contest id with id of task
+    /// in contest directory (`1850/a`)
+    pub fn locate_task(&self, address: &str) -> TaskLocation {
+        let address = address.to_lowercase();
+        let local = TaskLocation::Local(address.clone());
+        if self.tasks.contains_key(&address) {
+            return local;
+        }
+        let Some((contest, id)) = address.split_once('/') else {
+            return local;
+        };
+        if self.contest.as_ref().and_then(|c| c.id.as_deref()) == Some(contest) {
+            return TaskLocation::Local(id.into());
+        }
+        match self.contest_config_path(contest) {
+            Some(path) => TaskLocation::Workspace(path, id.into()),
+            None => local,
+        }
+    }
+    /// Path to config in contest directory: `dir` from `contests`, or subdirectory named by
+    /// contest id
+    fn contest_config_path(&self, contest: &str) -> Option<PathBuf> {
+        let dir = match self.contests.get(contest).and_then(|c| c.dir.as_ref()) {
+            Some(dir) => dir.clone(),
+            None => contest.into(),
+        };
+        let dir = self.dir.as_ref().map_or(dir.clone(), |d| d.join(dir));
+        Some(dir.join(CONFIG_FILE_NAME)).filter(|p| p.is_file())
+    }
+    /// Contest by id, from `contests` or `contest`
+    pub fn contest(&self, id: &str) -> Option<&Contest> {
+        self.contests.get(id).or(self
+            .contest
+            .as_ref()
+            .filter(|c| c.id.as_deref().unwrap_or_default() == id))
+    }
+    /// Id of contest of task: prefix of task id like `1850/a` or id from `contest`
+    pub(crate) fn task_contest<'s>(&'s self, id: &'s str) -> Option<&'s str> {
+        match id.split_once('/') {
+            Some((contest, _)) => Some(contest),
+            None => self
+                .contest
+                .as_ref()
+                .map(|c| c.id.as_deref().unwrap_or_default()),
+        }
+    }
+    /// Add tasks of contest with their samples, existing tests and names are kept. Contest is
+    /// saved to `contest` if config has no tasks, otherwise tasks are added to `contests` with
+    /// ids like `1850/a`, so contest id is required then. Returns ids of tasks
+    pub fn init_contest(
+        &mut self,
+        contest: Contest,
+        problems: Vec<Problem>,
+    ) -> Result<Vec<TaskID>> {
+        let same =
+            contest.id.is_some() && self.contest.as_ref().is_some_and(|c| c.id == contest.id);
+        let prefix = if same {
+            None
+        } else if self.contest.is_none() && self.tasks.is_empty() {
+            if contest.id.is_some() || contest.name.is_some() {
+                self.contest = Some(contest);
+            }
+            None
+        } else if let Some(id) = contest.id.clone() {
+            let contest = Contest {
+                id: None,
+                ..contest
+            };
+            self.contests.entry(id.clone()).or_insert(contest);
+            Some(id)
+        } else {
+            // problems would be mixed with existing tasks
+            return Err(Error::ContestIdRequired);
+        };
+
+        let mut ids = vec![];
+        for problem in problems {
+            let id = match &prefix {
+                Some(contest) => format!("{contest}/{}", problem.id),
+                None => problem.id.clone(),
+            }
+            .to_lowercase();
+            let is_new = !self.tasks.contains_key(&id);
+            let task = self.tasks.entry(id.clone()).or_default();
+            if !problem.name.is_empty() {
+                task.name = problem.name;
+            } else if is_new {
+                task.name = format!("Problem {}", problem.id.to_uppercase());
+            }
+            for test in problem.tests {
+                if !task
+                    .tests
+                    .iter()
+                    .any(|t| t.input.trim() == test.input.trim())
+                {
+                    task.tests.push(test);
+                }
+            }
+            ids.push(id);
+        }
+        Ok(ids)
+    }
+    /// Configs of contests stored in directories, by contest id
+    pub fn contest_configs(&self) -> Vec<(&str, PathBuf)> {
+        self.contests
+            .iter()
+            .filter(|(_, c)| c.dir.is_some())
+            .filter_map(|(id, _)| Some((id.as_str(), self.contest_config_path(id)?)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn problem(id: &str, name: &str) -> Problem {
+        Problem {
+            id: id.into(),
+            name: name.into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn init_contest_keeps_names_of_existing_tasks() {
+        let mut config = Config::default();
+        let contest = Contest {
+            id: Some("1850".into()),
+            ..Default::default()
+        };
+        config
+            .init_contest(contest.clone(), vec![problem("a", "Echo")])
+            .unwrap();
+        let ids = config
+            .init_contest(contest, vec![problem("a", ""), problem("b", "")])
+            .unwrap();
+        assert_eq!(ids, ["a", "b"]);
+        assert_eq!(config.get_task_name(&"a".into()).unwrap(), "Echo");
+        assert_eq!(config.get_task_name(&"b".into()).unwrap(), "Problem B");
+    }
+
+    #[test]
+    fn init_contest_requires_id_for_config_with_tasks() {
+        let mut config = Config::default();
+        config.add_task(&"a".into(), "Echo");
+        let result = config.init_contest(Contest::default(), vec![problem("a", "")]);
+        assert!(matches!(result, Err(Error::ContestIdRequired)));
+        assert_eq!(config.get_task_name(&"a".into()).unwrap(), "Echo");
+    }
+
+    #[test]
+    fn init_contest_prefixes_tasks_of_other_contest() {
+        let mut config = Config::default();
+        config.add_task(&"a".into(), "Echo");
+        let contest = Contest {
+            id: Some("1851".into()),
+            ..Default::default()
+        };
+        let ids = config
+            .init_contest(contest, vec![problem("a", "")])
+            .unwrap();
+        assert_eq!(ids, ["1851/a"]);
+        assert_eq!(config.get_task_name(&"a".into()).unwrap(), "Echo");
+    }
+
+    #[test]
+    fn locates_tasks_of_workspace() {
+        let dir = crate::temp::TempDir::new("test").unwrap();
+        for contest in ["1851", "other", "1853"] {
+            std::fs::create_dir(dir.path().join(contest)).unwrap();
+        }
+        std::fs::write(dir.path().join("1851").join(CONFIG_FILE_NAME), "").unwrap();
+        std::fs::write(dir.path().join("other").join(CONFIG_FILE_NAME), "").unwrap();
+        let mut config = Config::try_from(
+            r#"
+[contest]
+id = "1850"
+
+[contests.1852]
+dir = "other"
+
+[tasks.a]
+name = "A"
+
+[tasks."1854/a"]
+name = "A"
+"#,
+        )
+        .unwrap();
+        config.dir = Some(dir.path().into());
+        let local = |id: &str| TaskLocation::Local(id.into());
+        let workspace = |contest: &str, id: &str| {
+            TaskLocation::Workspace(dir.path().join(contest).join(CONFIG_FILE_NAME), id.into())
+        };
+        assert_eq!(config.locate_task("A"), local("a"));
+        assert_eq!(config.locate_task("1850/B"), local("b"));
+        assert_eq!(config.locate_task("1854/a"), local("1854/a"));
+        assert_eq!(config.locate_task("1851/a"), workspace("1851", "a"));
+        assert_eq!(config.locate_task("1852/a"), workspace("other", "a"));
+        // directory without config and missing directory are not contests of workspace
+        assert_eq!(config.locate_task("1853/a"), local("1853/a"));
+        assert_eq!(config.locate_task("1855/a"), local("1855/a"));
+    }
+
+    #[test]
+    fn parses_problem_ids() {
+        assert_eq!(parse_problem_ids("a-d").unwrap(), ["a", "b", "c", "d"]);
+        assert_eq!(
+            parse_problem_ids("A, b,c1,C2").unwrap(),
+            ["a", "b", "c1", "c2"]
+        );
+        assert_eq!(parse_problem_ids("a-b,e").unwrap(), ["a", "b", "e"]);
+        assert!(parse_problem_ids("").unwrap().is_empty());
+        assert!(parse_problem_ids("d-a").is_none());
+        assert!(parse_problem_ids("a-c1").is_none());
+        assert!(parse_problem_ids("1-3").is_none());
+    }
+}
@@ -0,0 +1,205 @@
+//! Detection of crash reports in stderr: sanitizers, Rust panics and failed assertions
+
+use std::fmt::Display;
+
+/// Kind of runtime error, reported by program or runtime
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CrashKind {
+    /// AddressSanitizer or LeakSanitizer
+    AddressSanitizer,
+    UndefinedBehaviorSanitizer,
+    Panic,
+    /// `assert` of C and C++
+    Assertion,
+}
+
+#[derive(Debug, Clone)]
+pub struct CrashReport {
+    pub kind: CrashKind,
+    /// Summary line of report, e.g. `heap-buffer-overflow a.cpp:5:12 in main`
+    pub summary: String,
+    /// Top stack frame or location of error
+    pub frame: Option<String>,
+}
+
+impl Display for CrashKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::AddressSanitizer => write!(f, "AddressSanitizer"),
+            Self::UndefinedBehaviorSanitizer => write!(f, "UndefinedBehaviorSanitizer"),
+            Self::Panic => write!(f, "panic"),
+            Self::Assertion => write!(f, "assertion failed"),
+        }
+    }
+}
+
+impl Display for CrashReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.kind, self.summary)?;
+        if let Some(frame) = &self.frame {
+            write!(f, "\n  at {frame}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Find first crash report in stderr of program
+pub fn parse_crash(stderr: &str) -> Option<CrashReport> {
+    let lines: Vec<&str> = stderr.lines().map(str::trim).collect();
+    lines.iter().enumerate().find_map(|(i, line)| {
+        parse_sanitizer(line, &lines[i + 1..])
+            .or_else(|| parse_ubsan(line))
+            .or_else(|| parse_panic(line, lines.get(i + 1)))
+            .or_else(|| parse_assertion(line))
+    })
+}
+
+/// `==1==ERROR: AddressSanitizer: ...`, followed by stack and `SUMMARY: ...` line
+fn parse_sanitizer(line: &str, rest: &[&str]) -> Option<CrashReport> {
+    let (_, error) = line.split_once("ERROR: ")?;
+    let message = error
+        .strip_prefix("AddressSanitizer: ")
+        .or_else(|| error.strip_prefix("LeakSanitizer: "))?;
+    let summary = rest
+        .iter()
+        .find_map(|l| {
+            l.strip_prefix("SUMMARY: AddressSanitizer: ")
+                .or_else(|| l.strip_prefix("SUMMARY: LeakSanitizer: "))
+        })
+        .unwrap_or(message);
+    // `#0 0x4f5e2 in main /src/a.cpp:5:12`
+    let frame = rest.iter().find_map(|l| {
+        let frame = l.strip_prefix("#0 ")?;
+        Some(frame.split_once(" in ").map_or(frame, |(_, f)| f).into())
+    });
+    Some(CrashReport {
+        kind: CrashKind::AddressSanitizer,
+        summary: summary.into(),
+        frame,
+    })
+}
+
+/// `a.cpp:5:7: runtime error: signed integer overflow: ...`
+fn parse_ubsan(line: &str) -> Option<CrashReport> {
+    let (location, message) = line.split_once(": runtime error: ")?;
+    Some(CrashReport {
+        kind: CrashKind::UndefinedBehaviorSanitizer,
+        summary: message.into(),
+        frame: Some(location.into()),
+    })
+}
+
+/// `thread 'main' panicked at src/main.rs:2:5:` with message on the next line, or
+/// `thread 'main' panicked at 'message', src/main.rs:2:5` in older versions
+fn parse_panic(line: &str, next: Option<&&str>) -> Option<CrashReport> {
+    // thread id is printed after name in newer versions: `thread 'main' (42) panicked at`
+    let (_, rest) = line.strip_prefix("thread '")?.split_once(" panicked at ")?;
+    let (summary, location) = match rest.strip_suffix(':') {
+        Some(location) => (next.map_or("", |n| n), location),
+        None => match rest.strip_prefix('\'').and_then(|r| r.rsplit_once("', ")) {
+            Some((message, location)) => (message, location),
+            None => ("", rest),
+        },
+    };
+    Some(CrashReport {
+        kind: CrashKind::Panic,
+        summary: summary.into(),
+        frame: Some(location.into()),
+    })
+}
+
+/// `a.out: a.cpp:5: int main(): Assertion `x > 0' failed.`
+fn parse_assertion(line: &str) -> Option<CrashReport> {
+    let (location, assertion) = line.split_once(": Assertion `")?;
+    let condition = assertion.strip_suffix("' failed.")?;
+    // location is prefixed with program name
+    let location = location.split_once(": ").map_or(location, |(_, l)| l);
+    Some(CrashReport {
+        kind: CrashKind::Assertion,
+        summary: format!("`{condition}` is false"),
+        frame: Some(location.into()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_address_sanitizer() {
+        let stderr = "\
+=================================================================
+==1==ERROR: AddressSanitizer: heap-buffer-overflow on address 0x602000000014
+READ of size 4 at 0x602000000014 thread T0
+    #0 0x4f5e2 in main /src/a.cpp:5:12
+    #1 0x7f0a in __libc_start_main
+SUMMARY: AddressSanitizer: heap-buffer-overflow /src/a.cpp:5:12 in main
+";
+        let report = parse_crash(stderr).unwrap();
+        assert_eq!(report.kind, CrashKind::AddressSanitizer);
+        assert_eq!(
+            report.summary,
+            "heap-buffer-overflow /src/a.cpp:5:12 in main"
+        );
+        assert_eq!(report.frame.as_deref(), Some("main /src/a.cpp:5:12"));
+    }
+
+    #[test]
+    fn parses_leak_sanitizer() {
+        let stderr = "\
+==7==ERROR: LeakSanitizer: detected memory leaks
+Direct leak of 4 byte(s) in 1 object(s) allocated from:
+    #0 0x7f3a in malloc
+SUMMARY: LeakSanitizer: 4 byte(s) leaked in 1 allocation(s).
+";
+        let report = parse_crash(stderr).unwrap();
+        assert_eq!(report.kind, CrashKind::AddressSanitizer);
+        assert_eq!(report.summary, "4 byte(s) leaked in 1 allocation(s).");
+        assert_eq!(report.frame.as_deref(), Some("malloc"));
+    }
+
+    #[test]
+    fn parses_undefined_behavior_sanitizer() {
+        let stderr = "a.cpp:5:7: runtime error: signed integer overflow: \
+                      2147483647 + 1 cannot be represented in type 'int'";
+        let report = parse_crash(stderr).unwrap();
+        assert_eq!(report.kind, CrashKind::UndefinedBehaviorSanitizer);
+        assert!(report.summary.starts_with("signed integer overflow"));
+        assert_eq!(report.frame.as_deref(), Some("a.cpp:5:7"));
+    }
+
+    #[test]
+    fn parses_panic() {
+        let stderr = "\
+thread 'main' (42) panicked at src/main.rs:2:5:
+index out of bounds: the len is 3 but the index is 5
+note: run with `RUST_BACKTRACE=1` environment variable to display a backtrace
+";
+        let report = parse_crash(stderr).unwrap();
+        assert_eq!(report.kind, CrashKind::Panic);
+        assert_eq!(
+            report.summary,
+            "index out of bounds: the len is 3 but the index is 5"
+        );
+        assert_eq!(report.frame.as_deref(), Some("src/main.rs:2:5"));
+
+        let old = "thread 'main' panicked at 'explicit panic', src/main.rs:2:5";
+        let report = parse_crash(old).unwrap();
+        assert_eq!(report.summary, "explicit panic");
+        assert_eq!(report.frame.as_deref(), Some("src/main.rs:2:5"));
+    }
+
+    #[test]
+    fn parses_assertion() {
+        let stderr = "a.out: a.cpp:5: int main(): Assertion `x > 0' failed.";
+        let report = parse_crash(stderr).unwrap();
+        assert_eq!(report.kind, CrashKind::Assertion);
+        assert_eq!(report.summary, "`x > 0` is false");
+        assert_eq!(report.frame.as_deref(), Some("a.cpp:5: int main()"));
+    }
+
+    #[test]
+    fn ignores_other_stderr() {
+        assert!(parse_crash("debug: x = 5\nwarning: something").is_none());
+    }
+}
@@ -0,0 +1,68 @@
+//! Dates in UTC, without timezone database
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Seconds since 1970-01-01
+pub(crate) fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+/// Current date, e.g. `2023-07-21`
+pub(crate) fn today() -> String {
+    format_date(now())
+}
+
+/// Date of timestamp, e.g. `2023-07-21`
+pub(crate) fn format_date(secs: u64) -> String {
+    let (y, m, d) = civil_from_days((secs / 86400) as i64);
+    format!("{y:04}-{m:02}-{d:02}")
+}
+
+/// Date and time of timestamp, e.g. `2023-07-21 14:35`
+pub(crate) fn format_datetime(secs: u64) -> String {
+    let minutes = secs % 86400 / 60;
+    format!(
+        "{} {:02}:{:02}",
+        format_date(secs),
+        minutes / 60,
+        minutes % 60
+    )
+}
+
+/// Date from days since 1970-01-01, see <http://howardhinnant.github.io/date_algorithms.html>
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let y = yoe + era * 400 + i64::from(m <= 2);
+    (y, m, d)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn civil_from_days_handles_leap_years() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(-1), (1969, 12, 31));
+        assert_eq!(civil_from_days(11016), (2000, 2, 29));
+        assert_eq!(civil_from_days(19559), (2023, 7, 21));
+        // 2100 is not a leap year
+        assert_eq!(civil_from_days(47540), (2100, 2, 28));
+        assert_eq!(civil_from_days(47541), (2100, 3, 1));
+    }
+
+    #[test]
+    fn formats_timestamp() {
+        assert_eq!(format_date(1689950100), "2023-07-21");
+        assert_eq!(format_datetime(1689950100), "2023-07-21 14:35");
+    }
+}
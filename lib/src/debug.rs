@@ -0,0 +1,36 @@
+//! Running debugger with input of test
+
+use std::fs::write;
+
+use crate::{exec::exec_interactive, temp::TempDir, Config, Error, Result, TaskID, Test};
+
+/// Build profile used by `cdf debug`, if it is set
+pub const DEBUG_PROFILE: &str = "debug";
+
+impl Config {
+    /// Test `n` of task, starting from 1. Generated tests follow tests of task
+    pub fn test(&self, id: &TaskID, n: usize) -> Result<Test> {
+        self.all_tests(id)?
+            .into_iter()
+            .nth(n.wrapping_sub(1))
+            .ok_or_else(|| Error::TestNotFound(id.clone(), n))
+    }
+    /// Run debugger from build settings of task attached to terminal, with input of test `n`
+    /// (starting from 1) saved to file for `{input}` placeholder. Returns `true` if debugger
+    /// exited successfully
+    pub fn debug(&self, id: &TaskID, n: usize) -> Result<bool> {
+        let settings = self.build_settings(id)?;
+        let Some(debugger) = &settings.debugger else {
+            return Err(Error::DebuggerNotSet(self.task_language(id).into()));
+        };
+        let test = self.test(id, n)?;
+
+        let dir = TempDir::new("debug")?;
+        let input = dir.path().join("input.txt");
+        write(&input, &test.input).map_err(Error::CannotWriteInput)?;
+        let cmd = debugger
+            .replace("{id}", id)
+            .replace("{input}", &input.display().to_string());
+        exec_interactive(cmd, self.prepare_from_dir(settings, &self.dir))
+    }
+}
@@ -0,0 +1,320 @@
+//! Parsing of compiler diagnostics from build output, in formats of GCC, Clang and rustc
+
+use std::{fmt::Display, path::PathBuf};
+
+use crate::{CommandOutput, Config, Severity, TaskID};
+
+/// Summary messages of rustc and cargo, which do not point to code
+const RUSTC_SUMMARIES: &[&str] = &[
+    "aborting due to",
+    "could not compile",
+    ") generated ",
+    "warning emitted",
+    "warnings emitted",
+];
+
+/// Position in source file, line and column start from 1
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceLocation {
+    pub file: PathBuf,
+    pub line: usize,
+    pub column: Option<usize>,
+}
+
+/// Error or warning of compiler
+#[derive(Debug, Clone)]
+pub struct BuildDiagnostic {
+    pub severity: Severity,
+    pub message: String,
+    /// `None` for messages without location, e.g. from linker
+    pub location: Option<SourceLocation>,
+}
+
+impl BuildDiagnostic {
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+}
+
+impl Display for SourceLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.file.display(), self.line)?;
+        if let Some(column) = self.column {
+            write!(f, ":{column}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for BuildDiagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(l) = &self.location {
+            write!(f, "{l}: ")?;
+        }
+        write!(f, "{}: {}", self.severity, self.message)
+    }
+}
+
+/// Find errors and warnings in output of compiler. Notes and context lines are skipped
+pub fn parse_diagnostics(output: &str) -> Vec<BuildDiagnostic> {
+    let mut diagnostics: Vec<BuildDiagnostic> = vec![];
+    // rustc prints location on the line after message
+    let mut pending_rustc = false;
+    for line in output.lines() {
+        if let Some(location) = line.trim_start().strip_prefix("--> ") {
+            if pending_rustc {
+                if let Some(d) = diagnostics.last_mut() {
+                    d.location = parse_location(location);
+                }
+            }
+            pending_rustc = false;
+            continue;
+        }
+        pending_rustc = false;
+        if let Some(d) = parse_rustc_header(line) {
+            if !RUSTC_SUMMARIES.iter().any(|s| d.message.contains(s)) {
+                diagnostics.push(d);
+                pending_rustc = true;
+            }
+        } else if let Some(d) = parse_gcc_line(line) {
+            diagnostics.push(d);
+        }
+    }
+    diagnostics
+}
+
+/// `error[E0425]: message` or `warning: message`
+fn parse_rustc_header(line: &str) -> Option<BuildDiagnostic> {
+    let (head, message) = line.split_once(": ")?;
+    let kind = head.split_once('[').map_or(head, |(k, _)| k);
+    let severity = severity(kind)?;
+    Some(BuildDiagnostic {
+        severity,
+        message: message.trim().into(),
+        location: None,
+    })
+}
+
+/// `file:line:column: error: message`, location may be missing for linker messages
+fn parse_gcc_line(line: &str) -> Option<BuildDiagnostic> {
+    let (prefix, kind, message) = ["fatal error", "error", "warning"]
+        .iter()
+        .find_map(|kind| {
+            let (prefix, message) = line.split_once(&format!(": {kind}: "))?;
+            Some((prefix, *kind, message))
+        })?;
+    Some(BuildDiagnostic {
+        severity: severity(kind)?,
+        message: message.trim().into(),
+        location: parse_location(prefix),
+    })
+}
+
+fn severity(kind: &str) -> Option<Severity> {
+    match kind {
+        "error" | "fatal error" => Some(Severity::Error),
+        "warning" => Some(Severity::Warning),
+        _ => None,
+    }
+}
+
+/// `file:line` or `file:line:column`, file may contain colons (e.g. `C:\src\a.cpp`)
+fn parse_location(s: &str) -> Option<SourceLocation> {
+    let mut parts = s.trim().rsplitn(3, ':');
+    let last = parts.next()?;
+    let second = parts.next()?;
+    let (file, line, column) = match (second.parse().ok(), parts.next()) {
+        // file:line:column
+        (Some(line), Some(file)) if last.parse::<usize>().is_ok() => {
+            (file, line, last.parse().ok())
+        }
+        _ => {
+            let line = last.parse().ok()?;
+            let file = s.trim().strip_suffix(last)?.strip_suffix(':')?;
+            (file, line, None)
+        }
+    };
+    if file.is_empty() {
+        return None;
+    }
+    Some(SourceLocation {
+        file: file.into(),
+        line,
+        column,
+    })
+}
+
+impl CommandOutput {
+    /// Number of compiler warnings in output
+    pub fn warnings(&self) -> usize {
+        [&self.stderr, &self.stdout]
+            .into_iter()
+            .flat_map(|s| parse_diagnostics(s))
+            .filter(|d| !d.is_error())
+            .count()
+    }
+}
+
+impl Config {
+    /// Diagnostics from output of build command, with paths relative to directory of build
+    pub fn build_diagnostics(&self, id: &TaskID, output: &CommandOutput) -> Vec<BuildDiagnostic> {
+        let dir = self
+            .build_settings(id)
+            .ok()
+            .and_then(|s| self.prepare_from_dir(s, &self.dir));
+        let mut diagnostics = parse_diagnostics(&output.stderr);
+        diagnostics.extend(parse_diagnostics(&output.stdout));
+        if let Some(dir) = dir {
+            for location in diagnostics.iter_mut().filter_map(|d| d.location.as_mut()) {
+                // absolute paths are kept by join
+                location.file = dir.join(&location.file);
+            }
+        }
+        diagnostics
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn location(file: &str, line: usize, column: usize) -> Option<SourceLocation> {
+        Some(SourceLocation {
+            file: file.into(),
+            line,
+            column: Some(column),
+        })
+    }
+
+    #[test]
+    fn fails_build_with_warnings_only_if_enabled() {
+        let dir = crate::temp::TempDir::new("test").unwrap();
+        let warning = "a.cpp:3:9: warning: unused variable 'x' [-Wunused-variable]";
+        std::fs::write(dir.path().join("warn.txt"), warning).unwrap();
+        let mut config = Config::try_from(
+            r#"
+[settings.build]
+build = "sh -c 'cat warn.txt >&2'"
+run = "true"
+
+[languages.strict]
+build = "sh -c 'cat warn.txt >&2'"
+run = "true"
+warnings_as_errors = true
+
+[languages.broken]
+build = "sh -c 'exit 1'"
+run = "true"
+warnings_as_errors = true
+
+[tasks.a]
+name = "A"
+
+[tasks.b]
+name = "B"
+language = "strict"
+
+[tasks.c]
+name = "C"
+language = "broken"
+"#,
+        )
+        .unwrap();
+        config.dir = Some(dir.path().into());
+        let build = |id: &str| config.build(&id.into()).unwrap().unwrap();
+
+        let lenient = build("a");
+        assert!(lenient.success);
+        assert_eq!(lenient.warnings(), 1);
+        let strict = build("b");
+        assert!(!strict.success);
+        assert_eq!(strict.warnings(), 1);
+        // failed build without warnings is not affected
+        let broken = build("c");
+        assert!(!broken.success);
+        assert_eq!(broken.warnings(), 0);
+    }
+
+    #[test]
+    fn parses_gcc() {
+        let output = "\
+a.cpp: In function 'int main()':
+a.cpp:4:12: error: 'y' was not declared in this scope
+    4 |     return y;
+      |            ^
+a.cpp:3:9: warning: unused variable 'x' [-Wunused-variable]
+    3 |     int x;
+      |         ^
+";
+        let diagnostics = parse_diagnostics(output);
+        assert_eq!(diagnostics.len(), 2);
+        assert!(diagnostics[0].is_error());
+        assert_eq!(diagnostics[0].message, "'y' was not declared in this scope");
+        assert_eq!(diagnostics[0].location, location("a.cpp", 4, 12));
+        assert_eq!(diagnostics[1].severity, Severity::Warning);
+        assert_eq!(diagnostics[1].location, location("a.cpp", 3, 9));
+    }
+
+    #[test]
+    fn parses_clang() {
+        let output = "\
+a.cpp:4:12: error: use of undeclared identifier 'y'
+    4 |     return y;
+      |            ^
+a.cpp:3:9: warning: unused variable 'x' [-Wunused-variable]
+    3 |     int x;
+      |         ^
+1 warning and 1 error generated.
+";
+        let diagnostics = parse_diagnostics(output);
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0].message, "use of undeclared identifier 'y'");
+        assert_eq!(diagnostics[1].location, location("a.cpp", 3, 9));
+    }
+
+    #[test]
+    fn parses_rustc() {
+        let output = "\
+warning: unused variable: `x`
+ --> a.rs:2:9
+  |
+2 |     let x = 1;
+  |         ^ help: if this is intentional, prefix it with an underscore: `_x`
+  |
+  = note: `#[warn(unused_variables)]` (part of `#[warn(unused)]`) on by default
+
+error[E0425]: cannot find value `y` in this scope
+ --> a.rs:3:5
+  |
+3 |     y
+  |     ^
+  |
+
+error: aborting due to 1 previous error; 1 warning emitted
+
+For more information about this error, try `rustc --explain E0425`.
+";
+        let diagnostics = parse_diagnostics(output);
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0].message, "unused variable: `x`");
+        assert_eq!(diagnostics[0].location, location("a.rs", 2, 9));
+        assert!(diagnostics[1].is_error());
+        assert_eq!(diagnostics[1].location, location("a.rs", 3, 5));
+    }
+
+    #[test]
+    fn skips_rustc_summaries() {
+        let output = "\
+warning: unused variable: `x`
+ --> a.rs:2:9
+
+warning: 1 warning emitted
+";
+        assert_eq!(parse_diagnostics(output).len(), 1);
+        let output = "\
+warning: `a` (bin \"a\") generated 2 warnings
+warning: 2 warnings emitted
+";
+        assert!(parse_diagnostics(output).is_empty());
+    }
+}
@@ -0,0 +1,295 @@
+//! Format-preserving updates of config file
+//!
+//! Config is serialized as usual, and then only changed values are copied to the document
+//! parsed from the original file, so comments, key order and strings style are kept
+
+use toml_edit::{Array, Decor, DocumentMut, InlineTable, Item, Table, Value};
+
+/// Update `old` document to have the same values as `new`, keeping formatting of unchanged
+/// values
+pub(crate) fn merge_document(old: &mut DocumentMut, new: &DocumentMut) {
+    merge_table(old.as_table_mut(), new.as_table(), 0);
+}
+
+/// Normalize whitespace in document, keeping comments
+pub(crate) fn format_document(doc: &mut DocumentMut) {
+    format_table(doc.as_table_mut());
+    let trailing = comment_lines(doc.trailing().as_str());
+    doc.set_trailing(trailing);
+}
+
+fn merge_table(old: &mut Table, new: &Table, position: isize) {
+    // new tables are placed after the last table inside of the parent
+    let position = last_position(old).unwrap_or(position);
+
+    old.retain(|key, _| new.contains_key(key));
+    for (key, new_item) in new.iter() {
+        match old.get_mut(key) {
+            Some(old_item) => merge_item(old_item, new_item, position),
+            None => {
+                let mut item = new_item.clone();
+                set_position(&mut item, position);
+                old.insert(key, item);
+            }
+        }
+    }
+}
+
+fn merge_item(old: &mut Item, new: &Item, position: isize) {
+    match (old, new) {
+        (Item::Table(old), Item::Table(new)) => merge_table(old, new, position),
+        (Item::ArrayOfTables(old), Item::ArrayOfTables(new)) => {
+            while old.len() > new.len() {
+                old.remove(old.len() - 1);
+            }
+            let position = old
+                .iter()
+                .filter_map(last_position)
+                .max()
+                .unwrap_or(position);
+            for (i, new) in new.iter().enumerate() {
+                match old.get_mut(i) {
+                    Some(old) => merge_table(old, new, position),
+                    None => {
+                        let mut table = new.clone();
+                        set_table_position(&mut table, position);
+                        old.push(table);
+                    }
+                }
+            }
+        }
+        // inline tables and arrays of inline tables written by user
+        (Item::Value(old), new) => {
+            if let Ok(new) = new.clone().into_value() {
+                merge_value(old, &new)
+            }
+        }
+        (old, new) => {
+            *old = new.clone();
+            set_position(old, position);
+        }
+    }
+}
+
+fn merge_value(old: &mut Value, new: &Value) {
+    match (old, new) {
+        (Value::InlineTable(old), Value::InlineTable(new)) => merge_inline_table(old, new),
+        (Value::Array(old), Value::Array(new)) => merge_array(old, new),
+        (old, new) => {
+            if !is_same_value(old, new) {
+                let decor = old.decor().clone();
+                *old = new.clone();
+                *old.decor_mut() = decor;
+            }
+        }
+    }
+}
+
+fn merge_inline_table(old: &mut InlineTable, new: &InlineTable) {
+    old.retain(|key, _| new.contains_key(key));
+    for (key, new_value) in new.iter() {
+        match old.get_mut(key) {
+            Some(old_value) => merge_value(old_value, new_value),
+            None => {
+                old.insert(key, new_value.clone());
+            }
+        }
+    }
+}
+
+fn merge_array(old: &mut Array, new: &Array) {
+    while old.len() > new.len() {
+        old.remove(old.len() - 1);
+    }
+    for (i, new) in new.iter().enumerate() {
+        match old.get_mut(i) {
+            Some(old) => merge_value(old, new),
+            None => old.push_formatted(new.clone()),
+        }
+    }
+}
+
+/// Compare values ignoring their representation (e.g. literal or basic string)
+fn is_same_value(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::String(a), Value::String(b)) => a.value() == b.value(),
+        (Value::Integer(a), Value::Integer(b)) => a.value() == b.value(),
+        (Value::Float(a), Value::Float(b)) => a.value() == b.value(),
+        (Value::Boolean(a), Value::Boolean(b)) => a.value() == b.value(),
+        (Value::Datetime(a), Value::Datetime(b)) => a.value() == b.value(),
+        _ => false,
+    }
+}
+
+fn last_position(table: &Table) -> Option<isize> {
+    let nested = table.iter().filter_map(|(_, item)| match item {
+        Item::Table(t) => last_position(t),
+        Item::ArrayOfTables(a) => a.iter().filter_map(last_position).max(),
+        _ => None,
+    });
+    nested.chain(table.position()).max()
+}
+
+fn set_position(item: &mut Item, position: isize) {
+    match item {
+        Item::Table(t) => set_table_position(t, position),
+        Item::ArrayOfTables(a) => a.iter_mut().for_each(|t| set_table_position(t, position)),
+        _ => (),
+    }
+}
+
+/// Set the same position for table and all nested tables, so they are kept together in the
+/// original order
+fn set_table_position(table: &mut Table, position: isize) {
+    table.set_position(position);
+    table
+        .iter_mut()
+        .for_each(|(_, item)| set_position(item, position));
+}
+
+fn format_table(table: &mut Table) {
+    let prefix = comment_lines(table.decor().prefix().and_then(|p| p.as_str()));
+    table.decor_mut().set_prefix(format!("\n{prefix}"));
+    table.decor_mut().set_suffix("");
+    for (mut key, item) in table.iter_mut() {
+        match item {
+            Item::Table(t) => format_table(t),
+            Item::ArrayOfTables(a) => a.iter_mut().for_each(format_table),
+            Item::Value(v) => {
+                let prefix = comment_lines(key.leaf_decor().prefix().and_then(|p| p.as_str()));
+                *key.leaf_decor_mut() = Decor::new(prefix, " ");
+                let suffix = trailing_comment(v.decor().suffix().and_then(|s| s.as_str()));
+                *v.decor_mut() = Decor::new(" ", suffix);
+            }
+            Item::None => (),
+        }
+    }
+}
+
+/// Keep only comments from whitespace and comments before an item
+fn comment_lines(s: Option<&str>) -> String {
+    s.unwrap_or_default()
+        .lines()
+        .map(str::trim)
+        .filter(|l| l.starts_with('#'))
+        .map(|l| format!("{l}\n"))
+        .collect()
+}
+
+fn trailing_comment(s: Option<&str>) -> String {
+    let s = s.unwrap_or_default().trim();
+    if s.starts_with('#') {
+        format!(" {s}")
+    } else {
+        String::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn merge(old: &str, new: &str) -> String {
+        let mut doc: DocumentMut = old.parse().unwrap();
+        merge_document(&mut doc, &new.parse().unwrap());
+        doc.to_string()
+    }
+
+    #[test]
+    fn keeps_comments_and_style_of_unchanged_values() {
+        let old = "\
+# tasks of round
+[tasks.a]
+name = 'Echo' # literal string
+tests = [{ input = \"1\", expected = \"1\" }]
+";
+        let new = "\
+[tasks.a]
+name = \"Echo\"
+tests = [{ input = \"1\", expected = \"1\" }, { input = \"2\", expected = \"2\" }]
+";
+        assert_eq!(
+            merge(old, new),
+            "\
+# tasks of round
+[tasks.a]
+name = 'Echo' # literal string
+tests = [{ input = \"1\", expected = \"1\" }, { input = \"2\", expected = \"2\" }]
+"
+        );
+    }
+
+    #[test]
+    fn replaces_changed_and_removes_deleted_values() {
+        let old = "\
+[tasks.a]
+name = \"Echo\" # comment
+time_limit = 1000
+
+[tasks.b]
+name = \"Sum\"
+";
+        let new = "\
+[tasks.a]
+name = \"Reverse\"
+";
+        assert_eq!(
+            merge(old, new),
+            "\
+[tasks.a]
+name = \"Reverse\" # comment
+"
+        );
+    }
+
+    #[test]
+    fn adds_new_tables_after_existing_ones() {
+        let old = "\
+[settings.build]
+run = \"./{id}\"
+
+[tasks.a]
+name = \"Echo\"
+";
+        let new = "\
+[settings.build]
+run = \"./{id}\"
+
+[tasks.a]
+name = \"Echo\"
+
+[tasks.b]
+name = \"Sum\"
+
+[[tasks.b.tests]]
+input = \"1 2\"
+expected = \"3\"
+";
+        assert_eq!(merge(old, new), new);
+    }
+
+    #[test]
+    fn formats_whitespace_keeping_comments() {
+        let mut doc: DocumentMut = "\
+[tasks.a]
+# name of task
+name   =    \"Echo\"    # comment
+
+
+# trailing comment
+"
+        .parse()
+        .unwrap();
+        format_document(&mut doc);
+        // tables are separated with empty line
+        assert_eq!(
+            doc.to_string().trim_start(),
+            "\
+[tasks.a]
+# name of task
+name = \"Echo\" # comment
+# trailing comment
+"
+        );
+    }
+}
@@ -1,9 +1,11 @@
-use std::io::Error as IOError;
+use std::{io::Error as IOError, path::PathBuf};
 
 pub type Result<T, E = Error> = std::result::Result<T, E>;
 
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
+    #[error("cancelled")]
+    Cancelled,
     #[error("empty command")]
     EmptyCommand,
 
@@ -11,6 +13,16 @@ pub enum Error {
     CannotCreateCommand(String, IOError),
     #[error("cannot save config: {0}")]
     CannotSaveConfig(IOError),
+    #[error("cannot read config: {0}")]
+    CannotReadConfig(IOError),
+    #[error("cannot parse config: {0}")]
+    CannotParseConfig(toml::de::Error),
+    #[error("config {} was changed on disk since it was loaded", .0.display())]
+    ConfigChangedOnDisk(PathBuf),
+    #[error("cannot read user config {}: {}", .0.display(), .1)]
+    CannotReadUserConfig(PathBuf, IOError),
+    #[error("cannot parse user config {}: {}", .0.display(), .1)]
+    CannotParseUserConfig(PathBuf, toml::de::Error),
     #[error("cannot write to stdin: {0}")]
     CannotWriteToStdin(IOError),
     #[error("cannot read from stdout: {0}")]
@@ -19,12 +31,88 @@ pub enum Error {
     CannotReadFromStderr(IOError),
     #[error("cannot get current directory: {0}")]
     CannotGetCwd(IOError),
+    #[error("cannot read template {}: {}", .0.display(), .1)]
+    CannotReadTemplate(PathBuf, IOError),
+    #[error("cannot create source {}: {}", .0.display(), .1)]
+    CannotCreateSource(PathBuf, IOError),
+    #[error("cannot read source {}: {}", .0.display(), .1)]
+    CannotReadSource(PathBuf, IOError),
+    #[error("cannot write bundle {}: {}", .0.display(), .1)]
+    CannotWriteBundle(PathBuf, IOError),
+    #[error("cannot create temporary directory: {0}")]
+    CannotCreateTempDir(IOError),
+    #[error("cannot write input of test: {0}")]
+    CannotWriteInput(IOError),
+    #[error("sandbox is supported only on Linux")]
+    SandboxUnsupported,
+    #[error("directory {} cannot be used in sandbox", .0.display())]
+    SandboxPath(PathBuf),
+    #[error("cannot save hack {}: {}", .0.display(), .1)]
+    CannotSaveHack(PathBuf, IOError),
+    #[error("generator failed with seed {0}:\n{1}")]
+    GeneratorFailed(u64, String),
+    #[error("input is invalid: {0}")]
+    InvalidInput(String),
+    #[error("input of generator with seed {0} is invalid: {1}")]
+    InvalidGeneratedInput(u64, String),
+    #[error("reference failed on input of generator with seed {0}:\n{1}")]
+    ReferenceFailed(u64, String),
+    #[error("cannot cache generated test {}: {}", .0.display(), .1)]
+    CannotCacheTest(PathBuf, IOError),
 
     #[error("task \"{0}\" not found")]
     TaskNotFound(String),
     #[error("no tests for task \"{0}\"")]
     TaskHasNoTests(String),
+    #[error("language \"{0}\" not found in languages")]
+    LanguageNotFound(String),
+    #[error("profile \"{0}\" not found in build settings")]
+    ProfileNotFound(String),
+    #[error("debugger is not set in build settings for \"{0}\"")]
+    DebuggerNotSet(String),
+    #[error("task \"{0}\" has no test {1}")]
+    TestNotFound(String, usize),
+    #[error("reference is not set for task \"{0}\", it is required for generated tests")]
+    ReferenceNotSet(String),
+    #[error("validator is not set for task \"{0}\"")]
+    ValidatorNotSet(String),
+    #[error("template for \"{0}\" not found in templates")]
+    TemplateNotFound(String),
+    #[error("source is not set in build settings for \"{0}\"")]
+    SourceNotSet(String),
+    #[error("source {} already exists", .0.display())]
+    SourceExists(PathBuf),
+
+    #[error("contest id is required to add problems to config with other tasks, set it with --id")]
+    ContestIdRequired,
+
+    #[error("submitting is not configured, add [settings.submit] to config")]
+    SubmitNotConfigured,
+    #[error("contest of task \"{0}\" is not set")]
+    ContestNotSet(String),
+    #[error("language_id is not set in build settings for \"{0}\"")]
+    LanguageIdNotSet(String),
+    #[error("cannot send request to server: {0}")]
+    SubmitRequest(String),
+    #[error("cannot parse response of server: {0}")]
+    CannotParseSubmitResponse(String),
+    #[error("server rejected request: {0}")]
+    SubmitRejected(String),
+    #[error("submission {0} not found in status of contest")]
+    SubmissionNotFound(u64),
+
+    #[error("cannot save history: {0}")]
+    CannotSaveHistory(IOError),
+    #[error("cannot read history: {0}")]
+    CannotReadHistory(IOError),
+
+    #[error("cannot save baselines: {0}")]
+    CannotSaveBaselines(IOError),
+    #[error("cannot read baselines: {0}")]
+    CannotReadBaselines(IOError),
 
     #[error("error serializing toml: {0}")]
     TomlSerialization(#[from] toml::ser::Error),
+    #[error("error updating toml document: {0}")]
+    TomlDocument(#[from] toml_edit::TomlError),
 }
@@ -3,9 +3,15 @@ use std::{
     io::{Read, Write},
     path::PathBuf,
     process::{Command, Stdio},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
 };
 
-use crate::{Error, Result};
+use crate::{sandbox::Sandbox, Error, Result};
 
 #[derive(Debug)]
 struct CommandConfig {
@@ -14,27 +20,60 @@ struct CommandConfig {
     cwd: PathBuf,
 }
 
+/// Interval of checking cancellation while command is running
+const CANCEL_POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Shared flag to stop running commands. Clones refer to the same flag
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Kill running command and do not start new ones
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst)
+    }
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
 #[derive(Debug)]
 pub struct CommandOutput {
     pub stdout: String,
     pub stderr: String,
     pub success: bool,
+    /// Wall time from spawning the command until it exited
+    pub time: Duration,
 }
 
 impl CommandOutput {
-    fn new(stdout: String, stderr: String, success: bool) -> Self {
+    fn new(stdout: String, stderr: String, success: bool, time: Duration) -> Self {
         Self {
             stdout,
             stderr,
             success,
+            time,
         }
     }
 }
 
-pub(crate) fn exec<S>(cmd: S, input: Option<S>, cwd: Option<PathBuf>) -> Result<CommandOutput>
+/// Execute command, killing it when `cancel` is cancelled
+pub(crate) fn exec<S>(
+    cmd: S,
+    input: Option<S>,
+    cwd: Option<PathBuf>,
+    cancel: &CancelToken,
+    sandbox: Option<&Sandbox>,
+) -> Result<CommandOutput>
 where
     S: Into<String>,
 {
+    if cancel.is_cancelled() {
+        return Err(Error::Cancelled);
+    }
     let cmd: String = cmd.into();
     let conf = prepare_exec(&cmd, cwd)?;
     let stdin = if input.is_some() {
@@ -42,47 +81,120 @@ where
     } else {
         Stdio::null()
     };
-    let mut child = Command::new(conf.name)
+    let mut command = Command::new(conf.name);
+    // changes of sandboxed command are removed when it exits
+    let _sandbox_dir = match sandbox {
+        Some(sandbox) => Some(sandbox.apply(&mut command, &conf.cwd)?),
+        None => None,
+    };
+    command
         .args(conf.args)
         .current_dir(conf.cwd)
         .stdin(stdin)
         .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
+        .stderr(Stdio::piped());
+    // own process group, so that processes started by command are killed with it
+    #[cfg(unix)]
+    std::os::unix::process::CommandExt::process_group(&mut command, 0);
+    let start = Instant::now();
+    let mut child = command
         .spawn()
         .map_err(|e| Error::CannotCreateCommand(cmd, e))?;
-    let mut stdout = child.stdout.take().expect("cannot get stdout");
-    let mut stderr = child.stderr.take().expect("cannot get stderr");
+    let pid = child.id();
+    let stdout = child.stdout.take().expect("cannot get stdout");
+    let stderr = child.stderr.take().expect("cannot get stderr");
+    // read outputs in background, so that command does not block on full pipe
+    let stdout = thread::spawn(move || read_all(stdout));
+    let stderr = thread::spawn(move || read_all(stderr));
 
-    if let Some(input) = input {
+    // write input in background too, so that command can be killed while it does not read
+    // input from full pipe
+    let writer = input.map(|input| {
         let mut stdin = child.stdin.take().expect("cannot get stdin");
         let input: String = input.into();
+        // stdin is closed when thread exits
+        thread::spawn(move || stdin.write_all(input.as_bytes()))
+    });
 
-        stdin
-            .write(input.as_bytes())
-            .map_err(Error::CannotWriteToStdin)?;
-        // close stdin
-        drop(stdin);
+    let waiter = thread::spawn(move || {
+        let status = child.wait();
+        (status.is_ok_and(|s| s.success()), start.elapsed())
+    });
+    while !waiter.is_finished() {
+        if cancel.is_cancelled() {
+            kill(pid);
+            let _ = waiter.join();
+            return Err(Error::Cancelled);
+        }
+        thread::sleep(CANCEL_POLL_INTERVAL);
     }
+    let (is_success, time) = waiter.join().unwrap_or((false, start.elapsed()));
 
+    if let Some(writer) = writer {
+        let written = writer
+            .join()
+            .unwrap_or_else(|_| Err(std::io::Error::other("writer thread panicked")));
+        // command may exit without reading input, then it is checked by its output
+        match written {
+            Err(e) if e.kind() != std::io::ErrorKind::BrokenPipe => {
+                return Err(Error::CannotWriteToStdin(e));
+            }
+            _ => (),
+        }
+    }
+
+    let output = join_output(stdout).map_err(Error::CannotReadFromStdout)?;
+    let err_output = join_output(stderr).map_err(Error::CannotReadFromStderr)?;
+    Ok(CommandOutput::new(output, err_output, is_success, time))
+}
+
+fn read_all<R: Read>(mut reader: R) -> std::io::Result<String> {
     let mut output = String::new();
-    stdout
-        .read_to_string(&mut output)
-        .map_err(Error::CannotReadFromStdout)?;
-    let mut err_output = String::new();
-    stderr
-        .read_to_string(&mut err_output)
-        .map_err(Error::CannotReadFromStderr)?;
-
-    let is_success = child.wait().map(|s| s.success()).unwrap_or(false);
-    Ok(CommandOutput::new(output, err_output, is_success))
+    reader.read_to_string(&mut output)?;
+    Ok(output)
+}
+
+fn join_output(reader: thread::JoinHandle<std::io::Result<String>>) -> std::io::Result<String> {
+    reader
+        .join()
+        .unwrap_or_else(|_| Err(std::io::Error::other("reader thread panicked")))
+}
+
+/// Kill process group of command
+#[cfg(unix)]
+fn kill(pid: u32) {
+    // SAFETY: sending signal has no memory safety requirements
+    unsafe {
+        libc::kill(-(pid as libc::pid_t), libc::SIGKILL);
+    }
+}
+
+/// Kill process with its children
+#[cfg(windows)]
+fn kill(pid: u32) {
+    let _ = Command::new("taskkill")
+        .args(["/F", "/T", "/PID", &pid.to_string()])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status();
+}
+
+/// Execute command attached to terminal, e.g. debugger. Returns `true` if it succeeded
+pub(crate) fn exec_interactive(cmd: String, cwd: Option<PathBuf>) -> Result<bool> {
+    let conf = prepare_exec(&cmd, cwd)?;
+    Command::new(conf.name)
+        .args(conf.args)
+        .current_dir(conf.cwd)
+        .status()
+        .map(|s| s.success())
+        .map_err(|e| Error::CannotCreateCommand(cmd, e))
 }
 
 fn prepare_exec<S: Into<String>>(cmd: S, cwd: Option<PathBuf>) -> Result<CommandConfig> {
     let cmd: String = cmd.into();
-    let cmd = cmd.split_whitespace();
-    let name = match cmd.clone().next() {
-        Some(c) => c,
-        None => return Err(Error::EmptyCommand),
+    let mut words = split_command(&cmd).into_iter();
+    let Some(name) = words.next() else {
+        return Err(Error::EmptyCommand);
     };
 
     let cwd = match cwd {
@@ -90,8 +202,97 @@ fn prepare_exec<S: Into<String>>(cmd: S, cwd: Option<PathBuf>) -> Result<Command
         None => current_dir().map_err(Error::CannotGetCwd)?,
     };
     Ok(CommandConfig {
-        name: name.into(),
-        args: cmd.skip(1).map(Into::into).collect(),
+        name,
+        args: words.collect(),
         cwd,
     })
 }
+
+/// Split command by whitespace, keeping words in single or double quotes together
+fn split_command(cmd: &str) -> Vec<String> {
+    split_words(cmd).0
+}
+
+/// Quote, which is not closed in command, e.g. `'` in `sh -c 'echo`. Rest of such command
+/// is one word
+pub(crate) fn unclosed_quote(cmd: &str) -> Option<char> {
+    split_words(cmd).1
+}
+
+/// Words of command and unclosed quote
+fn split_words(cmd: &str) -> (Vec<String>, Option<char>) {
+    let mut words = vec![];
+    let mut word: Option<String> = None;
+    let mut quote = None;
+    for c in cmd.chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), c) => word.get_or_insert_default().push(c),
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                word.get_or_insert_default();
+            }
+            (None, c) if c.is_whitespace() => words.extend(word.take()),
+            (None, c) => word.get_or_insert_default().push(c),
+        }
+    }
+    words.extend(word);
+    (words, quote)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_command_keeping_quoted_words() {
+        assert_eq!(
+            split_command(r#"gdb -ex "run < {input}" ./a.debug"#),
+            ["gdb", "-ex", "run < {input}", "./a.debug"]
+        );
+        assert_eq!(
+            split_command("  sh -c 'echo \"hi\"'  "),
+            ["sh", "-c", "echo \"hi\""]
+        );
+        assert_eq!(split_command(r#"echo "" a"b"c"#), ["echo", "", "abc"]);
+        assert!(split_command("   ").is_empty());
+    }
+
+    #[test]
+    fn finds_unclosed_quote() {
+        assert_eq!(unclosed_quote(r#"sh -c 'echo "hi"'"#), None);
+        assert_eq!(unclosed_quote("sh -c 'echo"), Some('\''));
+        assert_eq!(unclosed_quote(r#"echo "it's"#), Some('"'));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn passes_large_input() {
+        let input = "1".repeat(400_000);
+        let output = exec("wc -c", Some(&input), None, &CancelToken::new(), None).unwrap();
+        assert!(output.success);
+        assert_eq!(output.stdout.trim(), "400000");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn cancels_command_not_reading_large_input() {
+        let cancel = CancelToken::new();
+        let token = cancel.clone();
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(200));
+            token.cancel();
+        });
+        let input = "1".repeat(400_000);
+        let start = Instant::now();
+        let result = exec(
+            "sh -c 'while true; do :; done'",
+            Some(&input),
+            None,
+            &cancel,
+            None,
+        );
+        assert!(matches!(result, Err(Error::Cancelled)));
+        assert!(start.elapsed() < Duration::from_secs(5));
+    }
+}
@@ -0,0 +1,244 @@
+//! Tests generated on demand by generator command, cached next to config
+
+use std::{
+    collections::HashSet,
+    fs::{create_dir_all, read_dir, read_to_string, remove_file, write},
+    path::{Path, PathBuf},
+};
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::{exec::exec, history::hash, Config, Error, Result, TaskID, Test};
+
+/// Directory with generated tests, next to config
+pub const GENERATED_DIR_NAME: &str = "cdf.generated";
+
+/// Test, which input is printed by generator and expected output by reference solution of
+/// task. It is generated when it is run for the first time, and again when generator,
+/// reference or their sources change
+#[derive(Clone, Debug, Deserialize, Serialize, JsonSchema)]
+pub struct GeneratedTest {
+    /// Generator command with arguments. Placeholders: `{id}`, `{seed}`
+    pub generator: String,
+    /// Seed for generator, so that test is the same on every generation
+    pub seed: u64,
+}
+
+impl GeneratedTest {
+    /// Generator command with placeholders replaced
+    pub fn command(&self, id: &TaskID) -> String {
+        self.generator
+            .replace("{id}", id)
+            .replace("{seed}", &self.seed.to_string())
+    }
+}
+
+impl Config {
+    /// Tests of task followed by generated tests, which are generated if they are not cached
+    pub(crate) fn all_tests(&self, id: &TaskID) -> Result<Vec<Test>> {
+        let Some(task) = self.tasks.get(id) else {
+            return Ok(vec![]);
+        };
+        let mut tests = task.tests.clone();
+        if task.generated.is_empty() {
+            return Ok(tests);
+        }
+        let mut keys = HashSet::new();
+        for generated in &task.generated {
+            let key = self.generated_key(id, generated);
+            tests.push(self.generate_test(id, generated, &key)?);
+            keys.insert(key);
+        }
+        self.remove_stale_tests(id, &keys);
+        Ok(tests)
+    }
+    /// Generated test is cached and its generator has not changed since then
+    pub fn is_generated_cached(&self, id: &TaskID, test: &GeneratedTest) -> bool {
+        let (input, expected) = self.generated_paths(id, &self.generated_key(id, test));
+        input.is_file() && expected.is_file()
+    }
+    fn generate_test(&self, id: &TaskID, test: &GeneratedTest, key: &str) -> Result<Test> {
+        let (input_path, expected_path) = self.generated_paths(id, key);
+        if let (Ok(input), Ok(expected)) =
+            (read_to_string(&input_path), read_to_string(&expected_path))
+        {
+            return Ok(Test::new(input, expected));
+        }
+        let reference = self
+            .tasks
+            .get(id)
+            .and_then(|t| t.reference.as_ref())
+            .ok_or_else(|| Error::ReferenceNotSet(id.clone()))?;
+        let input = self.run_generator(id, test, &input_path)?;
+        self.check_generated_input(id, test.seed, &input)?;
+        let answer = exec(
+            reference.replace("{id}", id),
+            Some(input.clone()),
+            self.generator_cwd(id),
+            self.cancel_token(),
+            None,
+        )?;
+        if !answer.success {
+            return Err(Error::ReferenceFailed(test.seed, answer.stderr));
+        }
+        let expected = answer.stdout.trim().to_string();
+
+        if let Some(dir) = input_path.parent() {
+            create_dir_all(dir).map_err(|e| Error::CannotCacheTest(dir.into(), e))?;
+        }
+        write(&input_path, &input).map_err(|e| Error::CannotCacheTest(input_path.clone(), e))?;
+        write(&expected_path, &expected)
+            .map_err(|e| Error::CannotCacheTest(expected_path.clone(), e))?;
+        Ok(Test::new(input, expected))
+    }
+    /// Input of generated test, from cache if it is cached
+    pub(crate) fn generated_input(&self, id: &TaskID, test: &GeneratedTest) -> Result<String> {
+        let (input_path, _) = self.generated_paths(id, &self.generated_key(id, test));
+        self.run_generator(id, test, &input_path)
+    }
+    /// Read cached input from `path`, or print it with generator
+    fn run_generator(&self, id: &TaskID, test: &GeneratedTest, path: &Path) -> Result<String> {
+        if let Ok(input) = read_to_string(path) {
+            return Ok(input);
+        }
+        let output = exec(
+            test.command(id),
+            None,
+            self.generator_cwd(id),
+            self.cancel_token(),
+            None,
+        )?;
+        if !output.success {
+            return Err(Error::GeneratorFailed(test.seed, output.stderr));
+        }
+        Ok(output.stdout)
+    }
+    /// Hash of commands of generator and reference, and of files they mention (e.g.
+    /// `gen.py` in `python3 gen.py {seed}`), which changes when any of them is changed
+    fn generated_key(&self, id: &TaskID, test: &GeneratedTest) -> String {
+        let reference = self
+            .tasks
+            .get(id)
+            .and_then(|t| t.reference.as_deref())
+            .unwrap_or_default()
+            .replace("{id}", id);
+        let command = test.command(id);
+        let cwd = self.generator_cwd(id).unwrap_or_default();
+        let mut data = [command.as_bytes(), b"\0", reference.as_bytes()].concat();
+        for word in command
+            .split_whitespace()
+            .chain(reference.split_whitespace())
+        {
+            if let Ok(content) = std::fs::read(cwd.join(word)) {
+                data.push(0);
+                data.extend(content);
+            }
+        }
+        hash(&data)
+    }
+    /// Paths of cached input and expected output
+    fn generated_paths(&self, id: &TaskID, key: &str) -> (PathBuf, PathBuf) {
+        let dir = self.generated_dir(id);
+        (
+            dir.join(format!("{key}.in")),
+            dir.join(format!("{key}.out")),
+        )
+    }
+    fn generated_dir(&self, id: &TaskID) -> PathBuf {
+        let dir = Path::new(GENERATED_DIR_NAME).join(id);
+        self.dir.as_ref().map_or(dir.clone(), |d| d.join(&dir))
+    }
+    /// Generators are run in working directory of task
+    fn generator_cwd(&self, id: &TaskID) -> Option<PathBuf> {
+        self.build_settings(id)
+            .ok()
+            .and_then(|s| self.prepare_from_dir(s, &self.dir))
+    }
+    /// Remove cached tests, which generators were changed or removed
+    fn remove_stale_tests(&self, id: &TaskID, keys: &HashSet<String>) {
+        let Ok(entries) = read_dir(self.generated_dir(id)) else {
+            return;
+        };
+        for path in entries.filter_map(|e| e.ok()).map(|e| e.path()) {
+            let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+            if path.is_file() && !keys.contains(stem.as_ref()) {
+                let _ = remove_file(&path);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::temp::TempDir;
+
+    const CONFIG: &str = r#"
+[settings.build]
+run = "sh {id}.sh"
+
+[tasks.a]
+name = "Sum"
+reference = "sh ref.sh"
+generated = [{ generator = "sh gen.sh {seed}", seed = 2 }]
+"#;
+
+    fn config(dir: &TempDir) -> Config {
+        write(dir.path().join("gen.sh"), "echo $1 $1").unwrap();
+        write(dir.path().join("ref.sh"), "read a b; echo $((a + b))").unwrap();
+        let mut config = Config::try_from(CONFIG).unwrap();
+        config.dir = Some(dir.path().into());
+        config
+    }
+
+    #[test]
+    fn generated_key_changes_with_commands_and_files() {
+        let dir = TempDir::new("test").unwrap();
+        let mut config = config(&dir);
+        let id = "a".into();
+        let test = config.tasks["a"].generated[0].clone();
+        let key = config.generated_key(&id, &test);
+        assert_eq!(config.generated_key(&id, &test), key);
+
+        let other_seed = GeneratedTest {
+            seed: 3,
+            ..test.clone()
+        };
+        assert_ne!(config.generated_key(&id, &other_seed), key);
+
+        write(dir.path().join("gen.sh"), "echo $1 1").unwrap();
+        let changed_generator = config.generated_key(&id, &test);
+        assert_ne!(changed_generator, key);
+
+        config.tasks.get_mut("a").unwrap().reference = Some("sh ref.sh --fast".into());
+        assert_ne!(config.generated_key(&id, &test), changed_generator);
+    }
+
+    #[test]
+    fn generated_tests_are_cached_and_pruned() {
+        let dir = TempDir::new("test").unwrap();
+        let config = config(&dir);
+        let id = "a".into();
+        let test = config.tasks["a"].generated[0].clone();
+        assert!(!config.is_generated_cached(&id, &test));
+
+        let tests = config.all_tests(&id).unwrap();
+        assert_eq!(tests[0].input, "2 2\n");
+        assert_eq!(tests[0].expected, "4");
+        assert!(config.is_generated_cached(&id, &test));
+        let (_, expected) = config.generated_paths(&id, &config.generated_key(&id, &test));
+        write(&expected, "cached").unwrap();
+        assert_eq!(config.all_tests(&id).unwrap()[0].expected, "cached");
+
+        // test is generated again when reference changes
+        write(dir.path().join("ref.sh"), "exit 1").unwrap();
+        assert!(matches!(
+            config.all_tests(&id),
+            Err(Error::ReferenceFailed(2, _))
+        ));
+        write(dir.path().join("ref.sh"), "read a b; echo $((a * b + 1))").unwrap();
+        assert_eq!(config.all_tests(&id).unwrap()[0].expected, "5");
+        assert!(!expected.exists());
+    }
+}
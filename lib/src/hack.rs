@@ -0,0 +1,207 @@
+//! Hacking of foreign solutions: search for input, on which they give wrong output
+
+use std::{
+    fs::{create_dir_all, read_to_string, write},
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    exec::{exec, CommandOutput},
+    temp::TempDir,
+    Config, Error, FailedTest, Result, TaskID,
+};
+
+/// Default number of generated inputs, tried after tests of task
+pub const DEFAULT_HACK_ATTEMPTS: u64 = 100;
+
+/// Result of testing foreign solution
+#[derive(Debug)]
+pub enum HackResult {
+    /// Foreign solution cannot be built
+    BuildFailed(CommandOutput),
+    /// Foreign solution fails test of task
+    FailsTest(FailedTest),
+    /// Foreign solution fails input of generator with `seed`, expected output is output of
+    /// our solution
+    FailsGenerated { seed: u64, failed: FailedTest },
+    /// Foreign solution passed all valid tests and generated inputs, inputs rejected by
+    /// validator are skipped
+    Passed { tests: usize, generated: u64 },
+}
+
+impl Config {
+    /// Build foreign solution from `source` in temporary directory with build settings of
+    /// task, and run it on tests of task, then on `attempts` inputs from generator of task,
+    /// skipping inputs rejected by validator of task. Task itself must be built, it is used as reference
+    /// for generated inputs, and its runtime error is returned as error
+    pub fn hack(&self, id: &TaskID, source: &Path, attempts: u64) -> Result<HackResult> {
+        let settings = self.build_settings(id)?;
+        let Some(name) = &settings.source else {
+            return Err(Error::SourceNotSet(self.task_language(id).into()));
+        };
+        let content =
+            read_to_string(source).map_err(|e| Error::CannotReadSource(source.into(), e))?;
+        let dir = TempDir::new("hack")?;
+        let path = dir.path().join(name.replace("{id}", id));
+        if let Some(parent) = path.parent() {
+            create_dir_all(parent).map_err(|e| Error::CannotCreateSource(path.clone(), e))?;
+        }
+        write(&path, content).map_err(|e| Error::CannotCreateSource(path.clone(), e))?;
+
+        let cwd = Some(dir.path().to_path_buf());
+        if let Some(build) = self.build_in(id, cwd.clone())? {
+            if !build.success {
+                return Ok(HackResult::BuildFailed(build));
+            }
+        }
+        let literal = self.tasks.get(id).map_or(0, |t| t.tests.len());
+        let strict = self.fails_on_runtime_error(id);
+        let mut tests = 0;
+        for (index, test) in self.all_tests(id)?.into_iter().enumerate() {
+            // generated tests are checked when they are generated
+            if index < literal && !self.is_valid_input(id, &test.input)? {
+                continue;
+            }
+            let output = self.run_in(id, test.input.clone(), cwd.clone())?;
+            if !output.is_accepted(&test.expected, strict) {
+                let mut failed = FailedTest::new(index, test.input, test.expected, output);
+                failed.generated = index >= literal;
+                return Ok(HackResult::FailsTest(failed));
+            }
+            tests += 1;
+        }
+
+        let Some(generator) = self.tasks.get(id).and_then(|t| t.generator.as_ref()) else {
+            return Ok(HackResult::Passed {
+                tests,
+                generated: 0,
+            });
+        };
+        let gen_cwd = self.prepare_from_dir(settings, &self.dir);
+        let mut generated = 0;
+        for seed in 1..=attempts {
+            let cmd = generator
+                .replace("{id}", id)
+                .replace("{seed}", &seed.to_string());
+            let output = exec(cmd, None, gen_cwd.clone(), self.cancel_token(), None)?;
+            if !output.success {
+                return Err(Error::GeneratorFailed(seed, output.stderr));
+            }
+            let input = output.stdout;
+            if !self.is_valid_input(id, &input)? {
+                continue;
+            }
+            generated += 1;
+            let reference = self.run(id, input.clone())?;
+            // output of crashed solution cannot be trusted as expected
+            if reference.is_runtime_error() {
+                return Err(Error::ReferenceFailed(seed, reference.stderr));
+            }
+            let output = self.run_in(id, input.clone(), cwd.clone())?;
+            if !output.is_accepted(&reference.stdout, strict) {
+                let failed = FailedTest::new(tests, input, reference.stdout.trim().into(), output);
+                return Ok(HackResult::FailsGenerated { seed, failed });
+            }
+        }
+        Ok(HackResult::Passed { tests, generated })
+    }
+    /// Save input of hack to `{id}.hack.txt` next to config, to upload it to server
+    pub fn save_hack(&self, id: &TaskID, input: &str) -> Result<PathBuf> {
+        let name = format!("{id}.hack.txt");
+        let path = self
+            .dir
+            .as_ref()
+            .map_or(name.clone().into(), |d| d.join(&name));
+        write(&path, input).map_err(|e| Error::CannotSaveHack(path.clone(), e))?;
+        Ok(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"
+[settings.build]
+run = "sh {id}.sh"
+source = "{id}.sh"
+
+[tasks.a]
+name = "Sum"
+generator = "sh gen.sh {seed}"
+validator = "sh valid.sh"
+
+[[tasks.a.tests]]
+input = "1 2"
+expected = "3"
+
+[[tasks.a.tests]]
+input = "500 2"
+expected = "502"
+"#;
+
+    /// Config of task `a` in temporary directory, foreign solution `other.sh` gives wrong
+    /// answer for `a` matching `condition`
+    fn hack(condition: &str) -> HackResult {
+        hack_with("read a b; echo $((a + b))", condition).unwrap()
+    }
+
+    /// Same as [`hack`], with our solution `a.sh` of `solution`
+    fn hack_with(solution: &str, condition: &str) -> Result<HackResult> {
+        let dir = TempDir::new("test").unwrap();
+        let files = [
+            ("a.sh", solution.to_string()),
+            ("gen.sh", "echo $(($1 * 60)) 1".into()),
+            ("valid.sh", "read a b; test $a -le 100".into()),
+            (
+                "other.sh",
+                format!("read a b; if [ {condition} ]; then echo 0; else echo $((a + b)); fi"),
+            ),
+        ];
+        for (name, content) in files {
+            write(dir.path().join(name), content).unwrap();
+        }
+        let mut config = Config::try_from(CONFIG).unwrap();
+        config.dir = Some(dir.path().into());
+        config.hack(&"a".into(), &dir.path().join("other.sh"), 3)
+    }
+
+    #[test]
+    fn hack_skips_invalid_inputs() {
+        // only test `1 2` and seed 1 are valid
+        let result = hack("$a -gt 100");
+        assert!(matches!(
+            result,
+            HackResult::Passed {
+                tests: 1,
+                generated: 1
+            }
+        ));
+    }
+
+    #[test]
+    fn hack_finds_generated_input() {
+        let HackResult::FailsGenerated { seed, failed } = hack("$a -eq 60") else {
+            panic!("solution is not hacked");
+        };
+        assert_eq!(seed, 1);
+        assert_eq!(failed.input.trim(), "60 1");
+        assert_eq!(failed.expected, "61");
+    }
+
+    #[test]
+    fn hack_finds_test() {
+        let HackResult::FailsTest(failed) = hack("$a -eq 1") else {
+            panic!("solution is not hacked");
+        };
+        assert_eq!(failed.index, 0);
+    }
+
+    #[test]
+    fn hack_fails_when_reference_crashes() {
+        // correct on tests, but exits with error on generated input `60 1`
+        let solution = "read a b; echo $((a + b)); test $a -ne 60";
+        let result = hack_with(solution, "$a -eq 60");
+        assert!(matches!(result, Err(Error::ReferenceFailed(1, _))));
+    }
+}
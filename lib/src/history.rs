@@ -0,0 +1,284 @@
+//! History of test runs, stored next to config as JSON lines
+
+use std::{
+    fs::{read_to_string, OpenOptions},
+    io::Write,
+    path::PathBuf,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    date::{format_datetime, now},
+    CommandOutput, Config, Error, Result, TaskID, TestResult, Verdict,
+};
+
+/// Name of history file, saved next to config
+pub const HISTORY_FILE_NAME: &str = "cdf.history.jsonl";
+
+/// Results of running tests of task
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Run {
+    pub task: TaskID,
+    /// Seconds since 1970-01-01
+    pub time: u64,
+    /// Hash of solution source, if it is set in build settings
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub solution_hash: Option<String>,
+    /// `None` if task has no build command
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub build: Option<BuildRecord>,
+    pub tests: Vec<TestRecord>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BuildRecord {
+    /// Duration of build in milliseconds
+    pub time_ms: u64,
+    pub warnings: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TestRecord {
+    pub verdict: Verdict,
+    /// Time of running program in milliseconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_ms: Option<u64>,
+    /// Hash of input of test, to compare verdicts of the same test
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_hash: Option<String>,
+}
+
+/// Verdict of test changed since previous run
+#[derive(Debug, Clone)]
+pub struct TestChange {
+    pub index: usize,
+    /// `None` for new tests
+    pub before: Option<Verdict>,
+    pub after: Verdict,
+}
+
+impl TestChange {
+    /// Test was accepted in previous run, but not now
+    pub fn is_regression(&self) -> bool {
+        self.before.as_ref().is_some_and(Verdict::is_accepted) && !self.after.is_accepted()
+    }
+}
+
+impl Run {
+    pub fn passed(&self) -> usize {
+        self.tests
+            .iter()
+            .filter(|t| t.verdict.is_accepted())
+            .count()
+    }
+    /// Changes of verdicts since `previous` run. Tests are matched by hash of input, so that
+    /// tests can be added, removed or reordered
+    pub fn changes(&self, previous: &Run) -> Vec<TestChange> {
+        self.tests
+            .iter()
+            .enumerate()
+            .filter_map(|(index, t)| {
+                let before = previous
+                    .tests
+                    .iter()
+                    .find(|p| p.input_hash.is_some() && p.input_hash == t.input_hash)
+                    .map(|p| p.verdict.clone());
+                (before.as_ref() != Some(&t.verdict)).then(|| TestChange {
+                    index,
+                    before,
+                    after: t.verdict.clone(),
+                })
+            })
+            .collect()
+    }
+    /// First 8 characters of solution hash, whole hash if it is shorter
+    pub fn short_solution_hash(&self) -> Option<&str> {
+        let hash = self.solution_hash.as_deref()?;
+        Some(hash.get(..8).unwrap_or(hash))
+    }
+    /// Date and time of run in UTC
+    pub fn format_time(&self) -> String {
+        format_datetime(self.time)
+    }
+    /// One character per test: `.` for accepted, `x` for other verdicts
+    pub fn format_tests(&self) -> String {
+        self.tests
+            .iter()
+            .map(|t| if t.verdict.is_accepted() { '.' } else { 'x' })
+            .collect()
+    }
+}
+
+impl Config {
+    fn history_path(&self) -> PathBuf {
+        self.dir
+            .as_ref()
+            .map_or(HISTORY_FILE_NAME.into(), |d| d.join(HISTORY_FILE_NAME))
+    }
+    /// Hashes of inputs of tests, in order of results of [`Config::run_tests`]. Generated
+    /// tests may be generated, if they are not cached
+    pub fn input_hashes(&self, id: &TaskID) -> Vec<String> {
+        self.all_tests(id)
+            .unwrap_or_default()
+            .iter()
+            .map(|t| hash(t.input.as_bytes()))
+            .collect()
+    }
+    /// Append results of building and running tests of task to history, with hashes of
+    /// inputs from [`Config::input_hashes`]
+    pub fn save_run(
+        &self,
+        id: &TaskID,
+        build: Option<&CommandOutput>,
+        results: &[TestResult],
+        input_hashes: &[String],
+    ) -> Result<Run> {
+        let tests = results
+            .iter()
+            .enumerate()
+            .map(|(i, r)| TestRecord {
+                verdict: r.verdict(),
+                time_ms: r.time().map(|t| t.as_millis() as u64),
+                input_hash: input_hashes.get(i).cloned(),
+            })
+            .collect();
+        let run = Run {
+            task: id.clone(),
+            time: now(),
+            solution_hash: self.solution_hash(id),
+            build: build.map(|b| BuildRecord {
+                time_ms: b.time.as_millis() as u64,
+                warnings: b.warnings(),
+            }),
+            tests,
+        };
+        let line = serde_json::to_string(&run).map_err(|e| Error::CannotSaveHistory(e.into()))?;
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.history_path())
+            .and_then(|mut f| writeln!(f, "{line}"))
+            .map_err(Error::CannotSaveHistory)?;
+        Ok(run)
+    }
+    /// Runs of task from history, oldest first
+    pub fn history(&self, id: &TaskID) -> Result<Vec<Run>> {
+        let path = self.history_path();
+        if !path.exists() {
+            return Ok(vec![]);
+        }
+        let content = read_to_string(path).map_err(Error::CannotReadHistory)?;
+        // lines that cannot be parsed (e.g. partially written) are skipped
+        Ok(content
+            .lines()
+            .filter_map(|l| serde_json::from_str::<Run>(l).ok())
+            .filter(|r| r.task == *id)
+            .collect())
+    }
+    /// Hash of solution source
+    pub(crate) fn solution_hash(&self, id: &TaskID) -> Option<String> {
+        let source = std::fs::read(self.source_path(id).ok()?).ok()?;
+        Some(hash(&source))
+    }
+}
+
+/// FNV-1a hash in hex
+pub(crate) fn hash(data: &[u8]) -> String {
+    let hash = data.iter().fold(0xcbf29ce484222325u64, |hash, b| {
+        (hash ^ u64::from(*b)).wrapping_mul(0x100000001b3)
+    });
+    format!("{hash:016x}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(solution_hash: Option<&str>) -> Run {
+        Run {
+            task: "a".into(),
+            time: 0,
+            solution_hash: solution_hash.map(Into::into),
+            build: None,
+            tests: vec![],
+        }
+    }
+
+    #[test]
+    fn short_solution_hash() {
+        assert_eq!(
+            run(Some("0123456789abcdef")).short_solution_hash(),
+            Some("01234567")
+        );
+        assert_eq!(run(Some("abc")).short_solution_hash(), Some("abc"));
+        assert_eq!(run(None).short_solution_hash(), None);
+    }
+
+    fn record(verdict: Verdict, input: &str) -> TestRecord {
+        TestRecord {
+            verdict,
+            time_ms: None,
+            input_hash: Some(hash(input.as_bytes())),
+        }
+    }
+
+    #[test]
+    fn changes_match_tests_by_input() {
+        let mut previous = run(None);
+        previous.tests = vec![
+            record(Verdict::Accepted, "1"),
+            record(Verdict::Accepted, "2"),
+            record(Verdict::WrongAnswer, "3"),
+        ];
+        // test `1` was removed and test `4` was added
+        let mut current = run(None);
+        current.tests = vec![
+            record(Verdict::WrongAnswer, "2"),
+            record(Verdict::WrongAnswer, "3"),
+            record(Verdict::Accepted, "4"),
+        ];
+        let changes: Vec<_> = current
+            .changes(&previous)
+            .into_iter()
+            .map(|c| (c.index, c.is_regression(), c.before, c.after))
+            .collect();
+        assert_eq!(
+            changes,
+            [
+                (0, true, Some(Verdict::Accepted), Verdict::WrongAnswer),
+                (2, false, None, Verdict::Accepted),
+            ]
+        );
+    }
+
+    #[test]
+    fn saves_run_with_hashes_of_inputs() {
+        let dir = crate::temp::TempDir::new("test").unwrap();
+        std::fs::write(dir.path().join("a.sh"), "read a b; echo $((a + b))").unwrap();
+        let mut config = Config::try_from(
+            r#"
+[settings.build]
+run = "sh {id}.sh"
+
+[tasks.a]
+name = "Sum"
+tests = [
+    { input = "1 2", expected = "3" },
+    { input = "2 2", expected = "5" },
+]
+"#,
+        )
+        .unwrap();
+        config.dir = Some(dir.path().into());
+        let id = "a".to_string();
+        let results: Vec<_> = config.run_tests(&id).into_iter().collect();
+        let input_hashes = config.input_hashes(&id);
+        assert_eq!(input_hashes, [hash(b"1 2"), hash(b"2 2")]);
+        let run = config.save_run(&id, None, &results, &input_hashes).unwrap();
+        let verdicts: Vec<_> = run.tests.iter().map(|t| t.verdict.clone()).collect();
+        assert_eq!(verdicts, [Verdict::Accepted, Verdict::WrongAnswer]);
+        assert_eq!(run.tests[1].input_hash, Some(hash(b"2 2")));
+        assert_eq!(config.history(&id).unwrap().len(), 1);
+    }
+}
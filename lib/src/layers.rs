@@ -0,0 +1,283 @@
+//! User-level config, merged under project config
+//!
+//! User config is stored in `$XDG_CONFIG_HOME/cdf/config.toml` (or `~/.config/cdf/config.toml`,
+//! `%APPDATA%\cdf\config.toml` on Windows) and has the same structure as project config.
+//! Values from project config override values from user config
+
+use std::{
+    env::var_os,
+    fs::read_to_string,
+    path::{Path, PathBuf},
+};
+
+use toml::{Table, Value};
+
+use crate::{Error, Result};
+
+const USER_CONFIG_DIR: &str = "cdf";
+const USER_CONFIG_FILE_NAME: &str = "config.toml";
+
+#[derive(Debug, Clone)]
+pub(crate) struct Layer {
+    pub(crate) path: PathBuf,
+    pub(crate) table: Table,
+}
+
+impl Layer {
+    /// Load user config, if it exists
+    pub(crate) fn load_user() -> Result<Option<Self>> {
+        let Some(path) = user_config_path() else {
+            return Ok(None);
+        };
+        if !path.is_file() {
+            return Ok(None);
+        }
+        let s = read_to_string(&path).map_err(|e| Error::CannotReadUserConfig(path.clone(), e))?;
+        let mut table: Table =
+            toml::from_str(&s).map_err(|e| Error::CannotParseUserConfig(path.clone(), e))?;
+        if let Some(dir) = path.parent() {
+            resolve_templates(&mut table, dir);
+        }
+        Ok(Some(Self { path, table }))
+    }
+}
+
+/// Where value of effective config came from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValueOrigin {
+    Project,
+    User(PathBuf),
+    Default,
+}
+
+/// Value of effective config with dotted key, e.g. `settings.build.run`
+#[derive(Debug, Clone)]
+pub struct ConfigValue {
+    pub key: String,
+    /// Value in TOML syntax
+    pub value: String,
+    pub origin: ValueOrigin,
+}
+
+fn user_config_path() -> Option<PathBuf> {
+    let dir = if cfg!(target_family = "windows") {
+        var_os("APPDATA").map(PathBuf::from)
+    } else {
+        var_os("XDG_CONFIG_HOME")
+            .filter(|d| !d.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| var_os("HOME").map(|h| PathBuf::from(h).join(".config")))
+    };
+    dir.map(|d| d.join(USER_CONFIG_DIR).join(USER_CONFIG_FILE_NAME))
+}
+
+/// Template paths in user config are relative to user config directory
+fn resolve_templates(table: &mut Table, dir: &Path) {
+    let Some(Value::Table(templates)) = table.get_mut("templates") else {
+        return;
+    };
+    for (_, path) in templates.iter_mut() {
+        if let Value::String(p) = path {
+            *p = dir.join(&*p).display().to_string();
+        }
+    }
+}
+
+/// Merge `top` table over `bottom`, nested tables are merged recursively
+pub(crate) fn layer_tables(bottom: &Table, top: Table) -> Table {
+    let mut result = bottom.clone();
+    for (key, value) in top {
+        let value = match (result.remove(&key), value) {
+            (Some(Value::Table(bottom)), Value::Table(top)) => {
+                Value::Table(layer_tables(&bottom, top))
+            }
+            (_, value) => value,
+        };
+        result.insert(key, value);
+    }
+    result
+}
+
+/// Remove values inherited from `user` config from `table`, so they are not saved to project
+/// config. Values present in `project` config are kept
+pub(crate) fn strip_inherited(table: &mut Table, user: &Table, project: &Table) {
+    let empty = Table::new();
+    for (key, user_value) in user {
+        let project_value = project.get(key);
+        match (table.get_mut(key), user_value) {
+            (Some(Value::Table(t)), Value::Table(user)) => {
+                let project = project_value.and_then(Value::as_table).unwrap_or(&empty);
+                strip_inherited(t, user, project);
+                if t.is_empty() && project_value.is_none() {
+                    table.remove(key);
+                }
+            }
+            (Some(value), user_value) if value == user_value && project_value.is_none() => {
+                table.remove(key);
+            }
+            _ => (),
+        }
+    }
+}
+
+/// Flatten effective config to dotted keys with origin of each value. Tasks are skipped
+pub(crate) fn config_values(
+    effective: &Table,
+    user: Option<&Layer>,
+    project: &Table,
+) -> Vec<ConfigValue> {
+    let mut values = vec![];
+    let user_table = user.map(|u| &u.table);
+    for (key, value) in effective.iter().filter(|(k, _)| *k != "tasks") {
+        flatten(
+            key.clone(),
+            value,
+            user.map(|u| u.path.as_path()),
+            user_table.and_then(|t| t.get(key)),
+            project.get(key),
+            &mut values,
+        );
+    }
+    values
+}
+
+fn flatten(
+    key: String,
+    value: &Value,
+    user_path: Option<&Path>,
+    user: Option<&Value>,
+    project: Option<&Value>,
+    values: &mut Vec<ConfigValue>,
+) {
+    if let Value::Table(table) = value {
+        for (k, v) in table {
+            flatten(
+                format!("{key}.{k}"),
+                v,
+                user_path,
+                user.and_then(|u| u.get(k)),
+                project.and_then(|p| p.get(k)),
+                values,
+            );
+        }
+        return;
+    }
+    let origin = match (project, user, user_path) {
+        (Some(_), _, _) => ValueOrigin::Project,
+        (None, Some(_), Some(path)) => ValueOrigin::User(path.into()),
+        _ => ValueOrigin::Default,
+    };
+    values.push(ConfigValue {
+        key,
+        value: value.to_string(),
+        origin,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const USER: &str = r#"
+[settings.build]
+build = "g++ {id}.cpp -o {id}.out"
+run = "./{id}.out"
+
+[templates]
+default = "template.cpp"
+"#;
+
+    const PROJECT: &str = r#"
+[settings.build]
+run = "./{id}"
+
+[tasks.a]
+name = "A"
+"#;
+
+    fn table(s: &str) -> Table {
+        toml::from_str(s).unwrap()
+    }
+
+    fn user() -> Layer {
+        Layer {
+            path: "/home/u/.config/cdf/config.toml".into(),
+            table: table(USER),
+        }
+    }
+
+    #[test]
+    fn project_values_override_user_values() {
+        let merged = layer_tables(&table(USER), table(PROJECT));
+        let build = merged["settings"]["build"].as_table().unwrap();
+        assert_eq!(build["run"].as_str(), Some("./{id}"));
+        assert_eq!(build["build"].as_str(), Some("g++ {id}.cpp -o {id}.out"));
+        assert_eq!(
+            merged["templates"]["default"].as_str(),
+            Some("template.cpp")
+        );
+        assert_eq!(merged["tasks"]["a"]["name"].as_str(), Some("A"));
+    }
+
+    #[test]
+    fn resolves_templates_relative_to_user_config() {
+        let mut user = table(USER);
+        resolve_templates(&mut user, Path::new("/home/u/.config/cdf"));
+        let expected = Path::new("/home/u/.config/cdf").join("template.cpp");
+        assert_eq!(
+            user["templates"]["default"].as_str(),
+            Some(expected.display().to_string().as_str())
+        );
+    }
+
+    #[test]
+    fn strips_inherited_values_on_save() {
+        let project = table(PROJECT);
+        let mut saved = layer_tables(&table(USER), project.clone());
+        // new task added after loading
+        saved["tasks"]
+            .as_table_mut()
+            .unwrap()
+            .insert("b".into(), Value::Table(table("name = \"B\"")));
+        strip_inherited(&mut saved, &table(USER), &project);
+        let mut expected = project;
+        expected["tasks"]
+            .as_table_mut()
+            .unwrap()
+            .insert("b".into(), Value::Table(table("name = \"B\"")));
+        assert_eq!(saved, expected);
+    }
+
+    #[test]
+    fn keeps_project_value_equal_to_inherited() {
+        let project = table("[settings.build]\nrun = \"./{id}.out\"");
+        let mut saved = layer_tables(&table(USER), project.clone());
+        strip_inherited(&mut saved, &table(USER), &project);
+        assert_eq!(saved, project);
+    }
+
+    #[test]
+    fn attributes_values_to_their_origin() {
+        let user = user();
+        let project = table(PROJECT);
+        let mut effective = layer_tables(&user.table, project.clone());
+        effective["settings"]["build"]
+            .as_table_mut()
+            .unwrap()
+            .insert("cwd".into(), Value::String(".".into()));
+        let values = config_values(&effective, Some(&user), &project);
+        let origins: Vec<(&str, &ValueOrigin)> =
+            values.iter().map(|v| (v.key.as_str(), &v.origin)).collect();
+        let user_origin = ValueOrigin::User(user.path.clone());
+        assert_eq!(
+            origins,
+            [
+                ("templates.default", &user_origin),
+                ("settings.build.build", &user_origin),
+                ("settings.build.run", &ValueOrigin::Project),
+                ("settings.build.cwd", &ValueOrigin::Default),
+            ]
+        );
+        assert_eq!(values[2].value, "\"./{id}\"");
+    }
+}
@@ -1,9 +1,46 @@
+mod bench;
+mod bundle;
+mod codeforces;
 mod config;
+mod contest;
+mod crash;
+mod date;
+mod debug;
+mod diagnostics;
+mod document;
 mod errors;
 mod exec;
+mod generated;
+mod hack;
+mod history;
+mod layers;
+mod sandbox;
+mod submit;
+mod temp;
+mod template;
+mod validate;
+mod validator;
+mod verdict;
 
-pub use config::{Config, FailedTest, TaskInfo, Test, TestResult};
+pub use bench::{Baseline, BenchResult, TestTiming, BENCH_FILE_NAME};
+pub use bundle::BundleCheck;
+pub use codeforces::{contest_url, ContestPage, Problem};
+pub use config::{
+    Config, FailedTest, TaskInfo, Test, TestResult, CONFIG_FILE_NAME, SCHEMA_FILE_NAME,
+};
+pub use contest::{parse_problem_ids, Contest, TaskLocation};
+pub use crash::{parse_crash, CrashKind, CrashReport};
+pub use debug::DEBUG_PROFILE;
+pub use diagnostics::{parse_diagnostics, BuildDiagnostic, SourceLocation};
 pub use errors::{Error, Result};
-pub use exec::CommandOutput;
+pub use exec::{CancelToken, CommandOutput};
+pub use generated::{GeneratedTest, GENERATED_DIR_NAME};
+pub use hack::{HackResult, DEFAULT_HACK_ATTEMPTS};
+pub use history::{BuildRecord, Run, TestChange, TestRecord, HISTORY_FILE_NAME};
+pub use layers::{ConfigValue, ValueOrigin};
+pub use submit::{Submission, SubmitTarget, Submitter, SUBMIT_TOKEN_ENV};
+pub use validate::{Diagnostic, Location, Severity};
+pub use validator::InvalidTest;
+pub use verdict::Verdict;
 
 pub type TaskID = String;
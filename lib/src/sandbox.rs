@@ -0,0 +1,437 @@
+//! Sandbox for running untrusted solutions on Linux: clean environment, private copy of
+//! working directory, hidden home directory, read-only file system, no network, own process
+//! IDs and limited number of processes
+
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::Config;
+
+const DEFAULT_MAX_PROCESSES: u64 = 64;
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize, JsonSchema)]
+/// Settings of sandbox for running solutions, supported only on Linux
+pub(crate) struct SandboxSettings {
+    /// Run solutions in sandbox, same as `--sandbox` flag
+    #[serde(default)]
+    pub(crate) enabled: bool,
+    /// Limit of processes and threads of solution, default is 64
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) max_processes: Option<u64>,
+}
+
+/// Restrictions of sandboxed command
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Sandbox {
+    max_processes: u64,
+}
+
+impl Config {
+    /// Sandbox for run commands, if it is enabled by `--sandbox` or settings
+    pub(crate) fn sandbox(&self) -> Option<Sandbox> {
+        let settings = self.settings.sandbox.as_ref();
+        if !self.is_sandboxed() {
+            return None;
+        }
+        Some(Sandbox {
+            max_processes: settings
+                .and_then(|s| s.max_processes)
+                .unwrap_or(DEFAULT_MAX_PROCESSES),
+        })
+    }
+    /// Run solutions in sandbox, regardless of settings
+    pub fn set_sandbox(&mut self, enabled: bool) {
+        self.sandbox = enabled
+    }
+    pub fn is_sandboxed(&self) -> bool {
+        self.sandbox || self.settings.sandbox.as_ref().is_some_and(|s| s.enabled)
+    }
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use std::{
+        ffi::{CStr, CString},
+        fs::{create_dir, set_permissions, Permissions},
+        io,
+        os::unix::{ffi::OsStrExt, fs::PermissionsExt, process::CommandExt},
+        path::{Path, PathBuf},
+        process::Command,
+    };
+
+    use super::Sandbox;
+    use crate::{temp::TempDir, Error, Result};
+
+    const MOUNT_ATTR_RDONLY: u64 = 0x1;
+    const OPEN_TREE_CLONE: libc::c_int = 0x1;
+    const MOVE_MOUNT_F_EMPTY_PATH: libc::c_int = 0x4;
+
+    /// `struct mount_attr` of `mount_setattr`
+    #[repr(C)]
+    struct MountAttr {
+        attr_set: u64,
+        attr_clr: u64,
+        propagation: u64,
+        userns_fd: u64,
+    }
+
+    /// Directory with changes made by sandboxed command, removed on drop
+    #[derive(Debug)]
+    pub(crate) struct SandboxDir(TempDir);
+
+    impl Drop for SandboxDir {
+        fn drop(&mut self) {
+            // overlayfs leaves directory without permissions, which cannot be removed
+            let _ = set_permissions(
+                self.0.path().join("work/work"),
+                Permissions::from_mode(0o700),
+            );
+        }
+    }
+
+    impl Sandbox {
+        /// Run `command` in new user, mount, network and PID namespaces. Working directory
+        /// `cwd` is replaced with its copy-on-write overlay, home directory is hidden by
+        /// empty tmpfs, and the rest of file system is mounted read-only
+        pub(crate) fn apply(&self, command: &mut Command, cwd: &Path) -> Result<SandboxDir> {
+            let cwd_bytes = cwd.as_os_str().as_bytes();
+            // `,` and `:` separate options and directories of overlay
+            if cwd_bytes.iter().any(|b| matches!(b, b',' | b':' | 0)) {
+                return Err(Error::SandboxPath(cwd.to_path_buf()));
+            }
+            let dir = TempDir::new("sandbox")?;
+            for name in ["upper", "work"] {
+                create_dir(dir.path().join(name)).map_err(Error::CannotCreateTempDir)?;
+            }
+            let options = [
+                b"lowerdir=".as_slice(),
+                cwd_bytes,
+                b",upperdir=",
+                dir.path().join("upper").as_os_str().as_bytes(),
+                b",workdir=",
+                dir.path().join("work").as_os_str().as_bytes(),
+                b",userxattr",
+            ]
+            .concat();
+            let options = CString::new(options).map_err(|_| Error::SandboxPath(cwd.into()))?;
+            let dir_path = cwd.as_os_str();
+            let home = home_dir();
+            // directories to recreate on tmpfs over home, leading to working directory
+            let cwd_dirs = match &home {
+                Some(home) => cwd.strip_prefix(home).ok().map(|relative| {
+                    let mut path = home.clone();
+                    relative
+                        .iter()
+                        .map(|name| {
+                            path.push(name);
+                            to_cstring(&path)
+                        })
+                        .collect::<Vec<_>>()
+                }),
+                None => None,
+            };
+            let home = home.as_deref().map(to_cstring);
+            let cwd = to_cstring(cwd);
+            // SAFETY: getuid and getgid cannot fail
+            let (uid, gid) = unsafe { (libc::getuid(), libc::getgid()) };
+            let uid_map = format!("{uid} {uid} 1");
+            let gid_map = format!("{gid} {gid} 1");
+            let max_processes = self.max_processes;
+
+            command
+                .env_clear()
+                .env("PATH", std::env::var_os("PATH").unwrap_or_default())
+                .env("HOME", dir_path)
+                .env("TMPDIR", dir_path);
+            // SAFETY: closure only calls async-signal-safe functions with prepared
+            // arguments, without allocating memory
+            unsafe {
+                command.pre_exec(move || {
+                    check(libc::unshare(
+                        libc::CLONE_NEWUSER
+                            | libc::CLONE_NEWNS
+                            | libc::CLONE_NEWNET
+                            | libc::CLONE_NEWIPC
+                            | libc::CLONE_NEWPID,
+                    ))?;
+                    write_file(c"/proc/self/setgroups", b"deny")?;
+                    write_file(c"/proc/self/uid_map", uid_map.as_bytes())?;
+                    write_file(c"/proc/self/gid_map", gid_map.as_bytes())?;
+                    // do not propagate mounts of sandbox to parent namespace
+                    check(libc::mount(
+                        std::ptr::null(),
+                        c"/".as_ptr(),
+                        std::ptr::null(),
+                        libc::MS_REC | libc::MS_PRIVATE,
+                        std::ptr::null(),
+                    ))?;
+                    check(libc::mount(
+                        c"overlay".as_ptr(),
+                        cwd.as_ptr(),
+                        c"overlay".as_ptr(),
+                        0,
+                        options.as_ptr().cast(),
+                    ))?;
+                    if let Some(home) = &home {
+                        hide_home(home, &cwd, cwd_dirs.as_deref())?;
+                    }
+                    set_read_only(c"/", libc::AT_RECURSIVE, true)?;
+                    set_read_only(&cwd, 0, false)?;
+                    // working directory was entered before overlay was mounted
+                    check(libc::chdir(cwd.as_ptr()))?;
+                    // only children enter new PID namespace, the first of them becomes its
+                    // init, which ignores signals without handlers, so command is run by
+                    // the second one
+                    fork_and_wait()?;
+                    fork_and_wait()?;
+                    let limit = libc::rlimit {
+                        rlim_cur: max_processes,
+                        rlim_max: max_processes,
+                    };
+                    check(libc::setrlimit(libc::RLIMIT_NPROC, &limit))
+                });
+            }
+            Ok(SandboxDir(dir))
+        }
+    }
+
+    /// Home directory of user, if it is set and exists
+    pub(super) fn home_dir() -> Option<PathBuf> {
+        let home = PathBuf::from(std::env::var_os("HOME")?);
+        (home.is_absolute() && home.parent().is_some() && home.is_dir()).then_some(home)
+    }
+
+    /// Path without NUL bytes, which are rejected earlier for working directory
+    fn to_cstring(path: &Path) -> CString {
+        CString::new(path.as_os_str().as_bytes()).unwrap_or_default()
+    }
+
+    /// Mount empty tmpfs over `home`. If working directory `cwd` is inside it, its
+    /// overlay is moved to the same path on tmpfs, creating `cwd_dirs` leading to it
+    unsafe fn hide_home(home: &CStr, cwd: &CStr, cwd_dirs: Option<&[CString]>) -> io::Result<()> {
+        let tree = match cwd_dirs {
+            Some(_) => {
+                let fd = libc::syscall(
+                    libc::SYS_open_tree,
+                    libc::AT_FDCWD,
+                    cwd.as_ptr(),
+                    OPEN_TREE_CLONE | libc::O_CLOEXEC | libc::AT_RECURSIVE,
+                ) as libc::c_int;
+                check(fd)?;
+                Some(fd)
+            }
+            None => None,
+        };
+        check(libc::mount(
+            c"tmpfs".as_ptr(),
+            home.as_ptr(),
+            c"tmpfs".as_ptr(),
+            libc::MS_NOSUID | libc::MS_NODEV,
+            c"mode=0755".as_ptr().cast(),
+        ))?;
+        let (Some(tree), Some(cwd_dirs)) = (tree, cwd_dirs) else {
+            return Ok(());
+        };
+        for dir in cwd_dirs {
+            check(libc::mkdir(dir.as_ptr(), 0o755))?;
+        }
+        let result = libc::syscall(
+            libc::SYS_move_mount,
+            tree,
+            c"".as_ptr(),
+            libc::AT_FDCWD,
+            cwd.as_ptr(),
+            MOVE_MOUNT_F_EMPTY_PATH,
+        );
+        libc::close(tree);
+        check(result as libc::c_int)
+    }
+
+    /// Fork, returning in child. Parent waits for child and exits with its status
+    unsafe fn fork_and_wait() -> io::Result<()> {
+        let pid = libc::fork();
+        check(pid)?;
+        if pid == 0 {
+            return Ok(());
+        }
+        // `Command` waits for closing of pipe, inherited by all forks, to detect exec
+        libc::syscall(libc::SYS_close_range, 3, libc::c_uint::MAX, 0);
+        let mut status = 0;
+        while libc::waitpid(pid, &mut status, 0) == -1 {
+            if io::Error::last_os_error().kind() != io::ErrorKind::Interrupted {
+                libc::_exit(1);
+            }
+        }
+        if libc::WIFEXITED(status) {
+            libc::_exit(libc::WEXITSTATUS(status));
+        }
+        libc::_exit(128 + libc::WTERMSIG(status))
+    }
+
+    fn check(result: libc::c_int) -> io::Result<()> {
+        if result == -1 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    unsafe fn write_file(path: &CStr, content: &[u8]) -> io::Result<()> {
+        let fd = libc::open(path.as_ptr(), libc::O_WRONLY | libc::O_CLOEXEC);
+        check(fd)?;
+        let written = libc::write(fd, content.as_ptr().cast(), content.len());
+        libc::close(fd);
+        if written != content.len() as isize {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    /// Set or clear read-only flag of mount at `path`, recursively with `AT_RECURSIVE`
+    unsafe fn set_read_only(path: &CStr, flags: libc::c_int, read_only: bool) -> io::Result<()> {
+        let (attr_set, attr_clr) = if read_only {
+            (MOUNT_ATTR_RDONLY, 0)
+        } else {
+            (0, MOUNT_ATTR_RDONLY)
+        };
+        let attr = MountAttr {
+            attr_set,
+            attr_clr,
+            propagation: 0,
+            userns_fd: 0,
+        };
+        let result = libc::syscall(
+            libc::SYS_mount_setattr,
+            libc::AT_FDCWD,
+            path.as_ptr(),
+            flags,
+            &attr as *const MountAttr,
+            size_of::<MountAttr>(),
+        );
+        check(result as libc::c_int)
+    }
+}
+
+/// Changes made by sandboxed command, removed on drop
+#[cfg(not(target_os = "linux"))]
+#[derive(Debug)]
+pub(crate) struct SandboxDir;
+
+#[cfg(not(target_os = "linux"))]
+impl Sandbox {
+    /// Sandbox is supported only on Linux
+    pub(crate) fn apply(
+        &self,
+        _command: &mut std::process::Command,
+        _cwd: &std::path::Path,
+    ) -> crate::Result<SandboxDir> {
+        Err(crate::Error::SandboxUnsupported)
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use std::{
+        fs::{create_dir_all, read_to_string, remove_dir_all, write},
+        net::TcpListener,
+        process::{Command, Stdio},
+    };
+
+    use super::*;
+    use crate::{
+        exec::{exec, CancelToken},
+        temp::TempDir,
+        CommandOutput,
+    };
+
+    const SANDBOX: Sandbox = Sandbox { max_processes: 64 };
+
+    fn run(cmd: &str, cwd: &std::path::Path, sandbox: Option<&Sandbox>) -> CommandOutput {
+        exec(cmd, None, Some(cwd.into()), &CancelToken::new(), sandbox).unwrap()
+    }
+
+    #[test]
+    fn discards_changes_and_denies_writes_outside() {
+        let dir = TempDir::new("test").unwrap();
+        let outside = TempDir::new("test").unwrap();
+        write(dir.path().join("a.txt"), "old").unwrap();
+        let cmd = format!(
+            "sh -c 'echo new > a.txt && cat a.txt && echo x > {}/b.txt'",
+            outside.path().display()
+        );
+        let output = run(&cmd, dir.path(), Some(&SANDBOX));
+        assert_eq!(output.stdout.trim(), "new");
+        assert!(!output.success);
+        assert_eq!(read_to_string(dir.path().join("a.txt")).unwrap(), "old");
+        assert!(!outside.path().join("b.txt").exists());
+    }
+
+    #[test]
+    fn denies_network() {
+        let dir = TempDir::new("test").unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let cmd = format!("bash -c 'echo > /dev/tcp/127.0.0.1/{port}'");
+        assert!(run(&cmd, dir.path(), None).success);
+        assert!(!run(&cmd, dir.path(), Some(&SANDBOX)).success);
+    }
+
+    #[test]
+    fn isolates_processes() {
+        let dir = TempDir::new("test").unwrap();
+        let output = run("sh -c 'echo $$'", dir.path(), Some(&SANDBOX));
+        assert_eq!(output.stdout.trim(), "2");
+
+        let mut outside = Command::new("sleep")
+            .arg("30")
+            .stdout(Stdio::null())
+            .spawn()
+            .unwrap();
+        let cmd = format!("kill -9 {}", outside.id());
+        let output = run(&cmd, dir.path(), Some(&SANDBOX));
+        let alive = outside.try_wait().unwrap().is_none();
+        let _ = outside.kill();
+        let _ = outside.wait();
+        assert!(!output.success);
+        assert!(alive);
+    }
+
+    #[test]
+    fn cancels_sandboxed_command() {
+        let dir = TempDir::new("test").unwrap();
+        let cancel = CancelToken::new();
+        let token = cancel.clone();
+        let canceller = std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(200));
+            token.cancel();
+        });
+        let start = std::time::Instant::now();
+        let result = exec(
+            "sh -c 'sleep 30 & sleep 30'",
+            None,
+            Some(dir.path().into()),
+            &cancel,
+            Some(&SANDBOX),
+        );
+        canceller.join().unwrap();
+        assert!(matches!(result, Err(crate::Error::Cancelled)));
+        assert!(start.elapsed() < std::time::Duration::from_secs(5));
+    }
+
+    #[test]
+    fn hides_home_except_working_directory() {
+        let Some(home) = linux::home_dir() else {
+            return;
+        };
+        let base = home.join(format!(".cdf-sandbox-test-{}", std::process::id()));
+        let cwd = base.join("task");
+        create_dir_all(&cwd).unwrap();
+        write(cwd.join("in.txt"), "input").unwrap();
+        let cmd = format!("sh -c 'ls -A {} && cat in.txt'", home.display());
+        let output = exec(&cmd, None, Some(cwd), &CancelToken::new(), Some(&SANDBOX));
+        remove_dir_all(&base).unwrap();
+        let output = output.unwrap();
+        assert!(output.success, "{}", output.stderr);
+        let name = base.file_name().unwrap().to_str().unwrap();
+        assert_eq!(output.stdout.lines().collect::<Vec<_>>(), [name, "input"]);
+    }
+}
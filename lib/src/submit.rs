@@ -0,0 +1,321 @@
+//! Submitting solutions to Codeforces-compatible server
+//!
+//! Solution is sent with `POST {endpoint}/contest/{contest}/submit` as form with
+//! `submittedProblemIndex`, `programTypeId` and `source` fields. Server responds with
+//! submission in envelope of Codeforces API: `{"status": "OK", "result": {"id": 1}}`, and its
+//! verdict is polled with `GET {endpoint}/api/contest.status?contestId={contest}`
+
+use std::{env::var, time::Duration};
+
+use schemars::JsonSchema;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use ureq::Agent;
+
+use crate::{Config, Error, Result, TaskID, Verdict};
+
+/// Environment variable with token, sent in `Authorization` header
+pub const SUBMIT_TOKEN_ENV: &str = "CDF_SUBMIT_TOKEN";
+const TIMEOUT: Duration = Duration::from_secs(30);
+/// Number of latest submissions requested when polling verdict
+const STATUS_COUNT: &str = "20";
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize, JsonSchema)]
+/// Settings of `cdf submit`
+pub(crate) struct SubmitSettings {
+    /// URL of Codeforces-compatible server, e.g. `http://localhost:8080`
+    pub(crate) endpoint: String,
+    /// Handle of user, to find submissions in status of contest
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) handle: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Submission {
+    pub id: u64,
+    /// `None` while submission is being tested
+    pub verdict: Option<Verdict>,
+    pub passed_tests: u32,
+}
+
+/// Problem of task on server
+#[derive(Debug, Clone)]
+pub struct SubmitTarget {
+    pub contest: String,
+    /// Problem index, e.g. `A`
+    pub problem: String,
+    pub language_id: u32,
+}
+
+#[derive(Debug)]
+pub struct Submitter {
+    endpoint: String,
+    handle: Option<String>,
+    token: Option<String>,
+    agent: Agent,
+}
+
+#[derive(Deserialize)]
+struct ApiResponse<T> {
+    status: String,
+    comment: Option<String>,
+    result: Option<T>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ApiSubmission {
+    id: u64,
+    verdict: Option<String>,
+    #[serde(default)]
+    passed_test_count: u32,
+}
+
+impl From<ApiSubmission> for Submission {
+    fn from(s: ApiSubmission) -> Self {
+        let verdict = s
+            .verdict
+            .filter(|v| v != "TESTING")
+            .map(|v| Verdict::from_codeforces(&v));
+        Self {
+            id: s.id,
+            verdict,
+            passed_tests: s.passed_test_count,
+        }
+    }
+}
+
+impl Config {
+    /// Client for server from submit settings
+    pub fn submitter(&self) -> Result<Submitter> {
+        let Some(settings) = &self.settings.submit else {
+            return Err(Error::SubmitNotConfigured);
+        };
+        let agent = Agent::config_builder()
+            .timeout_global(Some(TIMEOUT))
+            .http_status_as_error(false)
+            .build()
+            .into();
+        Ok(Submitter {
+            endpoint: settings.endpoint.trim_end_matches('/').into(),
+            handle: settings.handle.clone(),
+            token: var(SUBMIT_TOKEN_ENV).ok(),
+            agent,
+        })
+    }
+    /// Contest, problem index and language id of task
+    pub fn submit_target(&self, id: &TaskID) -> Result<SubmitTarget> {
+        let contest = self
+            .task_contest(id)
+            .filter(|c| !c.is_empty())
+            .ok_or_else(|| Error::ContestNotSet(id.clone()))?;
+        let problem = id.rsplit('/').next().unwrap_or(id).to_uppercase();
+        let language_id = self
+            .build_settings(id)?
+            .language_id
+            .ok_or_else(|| Error::LanguageIdNotSet(self.task_language(id).into()))?;
+        Ok(SubmitTarget {
+            contest: contest.into(),
+            problem,
+            language_id,
+        })
+    }
+}
+
+impl Submitter {
+    pub fn submit(&self, target: &SubmitTarget, source: &str) -> Result<Submission> {
+        let url = format!("{}/contest/{}/submit", self.endpoint, target.contest);
+        let mut request = self.agent.post(&url);
+        if let Some(token) = &self.token {
+            request = request.header("Authorization", &format!("Bearer {token}"));
+        }
+        let language_id = target.language_id.to_string();
+        let form = [
+            ("submittedProblemIndex", target.problem.as_str()),
+            ("programTypeId", language_id.as_str()),
+            ("source", source),
+        ];
+        let response = request
+            .send_form(form)
+            .map_err(|e| Error::SubmitRequest(e.to_string()))?;
+        let submission: ApiSubmission = parse_response(response)?;
+        Ok(submission.into())
+    }
+    /// Current state of submission
+    pub fn status(&self, target: &SubmitTarget, id: u64) -> Result<Submission> {
+        let url = format!("{}/api/contest.status", self.endpoint);
+        let mut request = self
+            .agent
+            .get(&url)
+            .query("contestId", &target.contest)
+            .query("from", "1")
+            .query("count", STATUS_COUNT);
+        if let Some(handle) = &self.handle {
+            request = request.query("handle", handle);
+        }
+        if let Some(token) = &self.token {
+            request = request.header("Authorization", &format!("Bearer {token}"));
+        }
+        let response = request
+            .call()
+            .map_err(|e| Error::SubmitRequest(e.to_string()))?;
+        let submissions: Vec<ApiSubmission> = parse_response(response)?;
+        submissions
+            .into_iter()
+            .find(|s| s.id == id)
+            .map(Into::into)
+            .ok_or(Error::SubmissionNotFound(id))
+    }
+}
+
+fn parse_response<T: DeserializeOwned>(
+    mut response: ureq::http::Response<ureq::Body>,
+) -> Result<T> {
+    let status = response.status();
+    let body = response
+        .body_mut()
+        .read_to_string()
+        .map_err(|e| Error::SubmitRequest(e.to_string()))?;
+    let response: ApiResponse<T> = serde_json::from_str(&body)
+        .map_err(|e| Error::CannotParseSubmitResponse(format!("{status}: {e}")))?;
+    match (response.status.as_str(), response.result) {
+        ("OK", Some(result)) => Ok(result),
+        _ => Err(Error::SubmitRejected(
+            response.comment.unwrap_or(response.status),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead, BufReader, Read, Write},
+        net::TcpListener,
+        thread::{spawn, JoinHandle},
+    };
+
+    use super::*;
+
+    /// Stub server answering requests with `bodies` in order, returns requests it received
+    fn serve(bodies: &[&'static str]) -> (Submitter, JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let endpoint = format!("http://{}", listener.local_addr().unwrap());
+        let bodies = bodies.to_vec();
+        let server = spawn(move || {
+            let mut requests = vec![];
+            for body in bodies {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request = String::new();
+                let mut length = 0;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if let Some((name, value)) = line.split_once(':') {
+                        if name.eq_ignore_ascii_case("content-length") {
+                            length = value.trim().parse().unwrap();
+                        }
+                    }
+                    request.push_str(&line);
+                    if line == "\r\n" {
+                        break;
+                    }
+                }
+                let mut content = vec![0; length];
+                reader.read_exact(&mut content).unwrap();
+                request.push_str(&String::from_utf8(content).unwrap());
+                requests.push(request);
+                write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n\
+                     Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                    body.len()
+                )
+                .unwrap();
+            }
+            requests
+        });
+        let submitter = Submitter {
+            endpoint,
+            handle: Some("tourist".into()),
+            token: Some("secret".into()),
+            agent: Agent::config_builder()
+                .timeout_global(Some(TIMEOUT))
+                .http_status_as_error(false)
+                .build()
+                .into(),
+        };
+        (submitter, server)
+    }
+
+    fn target() -> SubmitTarget {
+        SubmitTarget {
+            contest: "1850".into(),
+            problem: "A".into(),
+            language_id: 54,
+        }
+    }
+
+    #[test]
+    fn submits_and_polls_verdict() {
+        let (submitter, server) = serve(&[
+            r#"{"status": "OK", "result": {"id": 7}}"#,
+            r#"{"status": "OK", "result": [{"id": 7, "verdict": "TESTING", "passedTestCount": 2}]}"#,
+            r#"{"status": "OK", "result": [
+                {"id": 6, "verdict": "OK", "passedTestCount": 10},
+                {"id": 7, "verdict": "WRONG_ANSWER", "passedTestCount": 3}
+            ]}"#,
+        ]);
+        let submission = submitter.submit(&target(), "int main() {}").unwrap();
+        assert_eq!(submission.id, 7);
+        let testing = submitter.status(&target(), 7).unwrap();
+        assert_eq!(testing.verdict, None);
+        assert_eq!(testing.passed_tests, 2);
+        let tested = submitter.status(&target(), 7).unwrap();
+        assert_eq!(tested.verdict, Some(Verdict::WrongAnswer));
+        assert_eq!(tested.passed_tests, 3);
+
+        let requests = server.join().unwrap();
+        assert!(requests[0].starts_with("POST /contest/1850/submit "));
+        assert!(requests[0]
+            .to_lowercase()
+            .contains("authorization: bearer secret"));
+        assert!(requests[0].contains("submittedProblemIndex=A&programTypeId=54&source="));
+        assert!(requests[1].starts_with("GET /api/contest.status?contestId=1850&"));
+        assert!(requests[1].contains("handle=tourist"));
+    }
+
+    #[test]
+    fn reports_failed_response() {
+        let (submitter, server) = serve(&[
+            r#"{"status": "FAILED", "comment": "contestId: Contest with id 1850 not found"}"#,
+            r#"{"status": "OK", "result": []}"#,
+        ]);
+        let error = submitter.submit(&target(), "").unwrap_err();
+        assert!(
+            matches!(error, Error::SubmitRejected(ref c) if c.contains("Contest with id 1850"))
+        );
+        let error = submitter.status(&target(), 7).unwrap_err();
+        assert!(matches!(error, Error::SubmissionNotFound(7)));
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn parses_codeforces_verdicts() {
+        let verdicts = [
+            ("OK", Verdict::Accepted),
+            ("WRONG_ANSWER", Verdict::WrongAnswer),
+            ("RUNTIME_ERROR", Verdict::RuntimeError),
+            ("IDLENESS_LIMIT_EXCEEDED", Verdict::TimeLimitExceeded),
+            ("MEMORY_LIMIT_EXCEEDED", Verdict::MemoryLimitExceeded),
+            ("COMPILATION_ERROR", Verdict::CompilationError),
+            ("CHALLENGED", Verdict::Other("CHALLENGED".into())),
+        ];
+        for (s, verdict) in verdicts {
+            assert_eq!(Verdict::from_codeforces(s), verdict);
+        }
+        assert_eq!(
+            Verdict::from_codeforces("CHALLENGED").to_string(),
+            "challenged"
+        );
+    }
+}
@@ -0,0 +1,60 @@
+use std::{
+    env::temp_dir,
+    fs::{create_dir, remove_dir_all},
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    process,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{Error, Result};
+
+/// Temporary directory, removed on drop
+#[derive(Debug)]
+pub(crate) struct TempDir(PathBuf);
+
+impl TempDir {
+    /// Create new directory, never reusing existing one, which may belong to other
+    /// `TempDir` or other user
+    pub(crate) fn new(prefix: &str) -> Result<Self> {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.subsec_nanos());
+        let name = format!("cdf-{prefix}-{}-{nanos}", process::id());
+        let mut counter = 0;
+        loop {
+            let path = temp_dir().join(format!("{name}-{counter}"));
+            match create_dir(&path) {
+                Ok(()) => return Ok(Self(path)),
+                Err(e) if e.kind() == ErrorKind::AlreadyExists => counter += 1,
+                Err(e) => return Err(Error::CannotCreateTempDir(e)),
+            }
+        }
+    }
+    pub(crate) fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = remove_dir_all(&self.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn creates_distinct_directories() {
+        let dirs: Vec<_> = (0..100).map(|_| TempDir::new("test").unwrap()).collect();
+        let mut paths: Vec<_> = dirs.iter().map(TempDir::path).collect();
+        paths.sort();
+        paths.dedup();
+        assert_eq!(paths.len(), dirs.len());
+        let path = dirs[0].path().to_path_buf();
+        drop(dirs);
+        assert!(!path.exists());
+    }
+}
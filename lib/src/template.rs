@@ -0,0 +1,158 @@
+//! Solution sources created from templates
+
+use std::{
+    fs::{create_dir_all, read_to_string, write},
+    path::PathBuf,
+};
+
+use crate::{date::today, Config, Error, Result, TaskID};
+
+/// Template of tasks without language
+const DEFAULT_TEMPLATE: &str = "default";
+
+impl Config {
+    /// Path to solution source of task, from `source` of its build settings
+    pub fn source_path(&self, id: &TaskID) -> Result<PathBuf> {
+        let settings = self.build_settings(id)?;
+        let Some(source) = &settings.source else {
+            return Err(Error::SourceNotSet(self.task_language(id).into()));
+        };
+        let cwd = self.prepare_from_dir(settings, &self.dir);
+        let source = PathBuf::from(source.replace("{id}", id));
+        Ok(cwd.map_or(source.clone(), |d| d.join(source)))
+    }
+    /// Create solution source of task from template of its language. Existing source is
+    /// overwritten only with `force`
+    pub fn create_source(&self, id: &TaskID, force: bool) -> Result<PathBuf> {
+        let path = self.source_path(id)?;
+        if path.exists() && !force {
+            return Err(Error::SourceExists(path));
+        }
+        let language = self.task_language(id);
+        let Some(template) = self.templates.get(language) else {
+            return Err(Error::TemplateNotFound(language.into()));
+        };
+        let template = self
+            .dir
+            .as_ref()
+            .map_or(template.clone(), |d| d.join(template));
+        let content =
+            read_to_string(&template).map_err(|e| Error::CannotReadTemplate(template, e))?;
+
+        let contest = self
+            .task_contest(id)
+            .map(|c| self.contest(c).and_then(|c| c.name.as_deref()).unwrap_or(c));
+        let values = [
+            ("id", id.as_str()),
+            ("name", &self.get_task_name(id).unwrap_or_default()),
+            ("contest", contest.unwrap_or_default()),
+            ("date", &today()),
+        ];
+        let content = values.iter().fold(content, |content, (p, v)| {
+            content.replace(&format!("{{{p}}}"), v)
+        });
+
+        if let Some(dir) = path.parent() {
+            create_dir_all(dir).map_err(|e| Error::CannotCreateSource(path.clone(), e))?;
+        }
+        write(&path, content).map_err(|e| Error::CannotCreateSource(path.clone(), e))?;
+        Ok(path)
+    }
+    /// Language of task, or `default`
+    pub(crate) fn task_language(&self, id: &TaskID) -> &str {
+        self.tasks
+            .get(id)
+            .and_then(|t| t.language.as_deref())
+            .unwrap_or(DEFAULT_TEMPLATE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::temp::TempDir;
+
+    const CONFIG: &str = r#"
+[settings.build]
+run = "./{id}.out"
+source = "src/{id}.cpp"
+
+[languages.py]
+run = "python3 {id}.py"
+
+[languages.rs]
+run = "./{id}"
+source = "{id}.rs"
+
+[templates]
+default = "template.cpp"
+rs = "missing.rs"
+
+[contest]
+id = "1850"
+name = "Round 1"
+
+[tasks.a]
+name = "Echo"
+
+[tasks.b]
+name = "Sum"
+language = "py"
+
+[tasks.c]
+name = "Product"
+language = "rs"
+"#;
+
+    fn config(dir: &TempDir) -> Config {
+        write(
+            dir.path().join("template.cpp"),
+            "// {contest} {id}: {name}, {date}\n",
+        )
+        .unwrap();
+        let mut config = Config::try_from(CONFIG).unwrap();
+        config.dir = Some(dir.path().into());
+        config
+    }
+
+    #[test]
+    fn creates_source_from_template() {
+        let dir = TempDir::new("test").unwrap();
+        let config = config(&dir);
+        let path = config.create_source(&"a".into(), false).unwrap();
+        assert_eq!(path, dir.path().join("src/a.cpp"));
+        assert_eq!(
+            read_to_string(&path).unwrap(),
+            format!("// Round 1 a: Echo, {}\n", today())
+        );
+    }
+
+    #[test]
+    fn overwrites_existing_source_only_with_force() {
+        let dir = TempDir::new("test").unwrap();
+        let config = config(&dir);
+        let path = config.create_source(&"a".into(), false).unwrap();
+        write(&path, "solution").unwrap();
+        let result = config.create_source(&"a".into(), false);
+        assert!(matches!(result, Err(Error::SourceExists(p)) if p == path));
+        assert_eq!(read_to_string(&path).unwrap(), "solution");
+        config.create_source(&"a".into(), true).unwrap();
+        assert_ne!(read_to_string(&path).unwrap(), "solution");
+    }
+
+    #[test]
+    fn reports_missing_source_and_template() {
+        let dir = TempDir::new("test").unwrap();
+        let config = config(&dir);
+        let result = config.create_source(&"b".into(), false);
+        assert!(matches!(result, Err(Error::SourceNotSet(l)) if l == "py"));
+        let result = config.create_source(&"c".into(), false);
+        assert!(matches!(result, Err(Error::CannotReadTemplate(..))));
+        assert!(!dir.path().join("c.rs").exists());
+
+        let mut config = config;
+        config.templates.clear();
+        let result = config.create_source(&"a".into(), false);
+        assert!(matches!(result, Err(Error::TemplateNotFound(l)) if l == "default"));
+    }
+}
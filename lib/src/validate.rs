@@ -0,0 +1,451 @@
+//! Config validation with diagnostics pointing to location in config file
+
+use std::{fmt::Display, fs::read_to_string, ops::Range, path::Path};
+
+use toml_edit::{Document, Item};
+
+use crate::{
+    config::{BuildSettings, DEBUGGER_PLACEHOLDERS, GENERATOR_PLACEHOLDERS, PLACEHOLDERS},
+    exec::unclosed_quote,
+    Config, Error, Result, CONFIG_FILE_NAME,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// Line and column in config file, starting from 1
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Location {
+    pub line: usize,
+    pub column: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    /// Location is unknown for values from user config
+    pub location: Option<Location>,
+    pub suggestion: Option<String>,
+}
+
+impl Diagnostic {
+    fn new<S: Into<String>>(severity: Severity, message: S, location: Option<Location>) -> Self {
+        Self {
+            severity,
+            message: message.into(),
+            location,
+            suggestion: None,
+        }
+    }
+    fn error<S: Into<String>>(message: S, location: Option<Location>) -> Self {
+        Self::new(Severity::Error, message, location)
+    }
+    fn warning<S: Into<String>>(message: S, location: Option<Location>) -> Self {
+        Self::new(Severity::Warning, message, location)
+    }
+    fn suggest<S: Into<String>>(mut self, suggestion: S) -> Self {
+        self.suggestion = Some(suggestion.into());
+        self
+    }
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+}
+
+impl Display for Severity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Error => write!(f, "error"),
+            Self::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(l) = self.location {
+            write!(f, "{}:{}: ", l.line, l.column)?;
+        }
+        write!(f, "{}: {}", self.severity, self.message)?;
+        if let Some(s) = &self.suggestion {
+            write!(f, "\n  help: {s}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Config {
+    /// Load config and check it. Syntax errors are returned as diagnostics too
+    pub fn check(path: &Path) -> Result<Vec<Diagnostic>> {
+        match Self::load(path) {
+            Ok(config) => Ok(config.validate()),
+            Err(Error::CannotParseConfig(e)) => {
+                let source = read_to_string(path).map_err(Error::CannotReadConfig)?;
+                let location = e.span().map(|span| location(&source, span.start));
+                Ok(vec![Diagnostic::error(e.message().trim(), location)])
+            }
+            Err(e) => Err(e),
+        }
+    }
+    /// Find semantic problems in config, which otherwise would surface only when running
+    /// commands
+    pub fn validate(&self) -> Vec<Diagnostic> {
+        let source = self.source.as_deref().unwrap_or_default();
+        let doc = Document::parse(source).ok();
+        let locate = |path: &[&str]| {
+            doc.as_ref()
+                .and_then(|d| span(d.as_item(), path))
+                .map(|s| location(source, s.start))
+        };
+
+        let mut diagnostics = vec![];
+        let profiles = std::iter::once((vec!["settings", "build"], &self.settings.build)).chain(
+            self.languages
+                .iter()
+                .map(|(lang, b)| (vec!["languages", lang.as_str()], b)),
+        );
+        for (path, settings) in profiles {
+            self.validate_build_settings(&path, settings, &locate, &mut diagnostics);
+        }
+
+        for (id, task) in &self.tasks {
+            let location = locate(&["tasks", id]);
+            let lowercase = id.to_lowercase();
+            if *id != lowercase {
+                diagnostics.push(
+                    Diagnostic::warning(
+                        format!("task id \"{id}\" contains uppercase letters"),
+                        location,
+                    )
+                    .suggest(format!(
+                        "ids are lowercased when adding tasks, rename it to \"{lowercase}\""
+                    )),
+                );
+            }
+            if let Some(lang) = &task.language {
+                if !self.languages.contains_key(lang) {
+                    let available: Vec<&str> = self.languages.keys().map(|l| l.as_str()).collect();
+                    diagnostics.push(
+                        Diagnostic::error(
+                            format!("language \"{lang}\" of task \"{id}\" not found"),
+                            locate(&["tasks", id, "language"]),
+                        )
+                        .suggest(format!(
+                            "add [languages.{lang}] or use one of: {}",
+                            available.join(", ")
+                        )),
+                    );
+                }
+            }
+            // name of command, its key and placeholders available in it
+            let mut commands: Vec<(String, &str, &String, &[&str])> = vec![];
+            let optional = [
+                ("generator", &task.generator, GENERATOR_PLACEHOLDERS),
+                ("validator", &task.validator, PLACEHOLDERS),
+                ("reference", &task.reference, PLACEHOLDERS),
+            ];
+            for (key, cmd, allowed) in optional {
+                commands.extend(cmd.iter().map(|c| (key.to_string(), key, c, allowed)));
+            }
+            for (i, test) in task.generated.iter().enumerate() {
+                let name = format!("generated[{i}].generator");
+                commands.push((name, "generated", &test.generator, GENERATOR_PLACEHOLDERS));
+            }
+            for (name, key, cmd, allowed) in commands {
+                let location = locate(&["tasks", id, key]);
+                if let Some(quote) = unclosed_quote(cmd) {
+                    diagnostics.push(quote_warning(
+                        quote,
+                        &format!("tasks.{id}.{name}"),
+                        location,
+                    ));
+                }
+                for p in placeholders(cmd).filter(|p| !allowed.contains(p)) {
+                    let available: Vec<String> =
+                        allowed.iter().map(|p| format!("{{{p}}}")).collect();
+                    diagnostics.push(
+                        Diagnostic::error(
+                            format!("unknown placeholder {{{p}}} in tasks.{id}.{name}"),
+                            location,
+                        )
+                        .suggest(format!("available placeholders: {}", available.join(", "))),
+                    );
+                }
+            }
+            if !task.generated.is_empty() && task.reference.is_none() {
+                diagnostics.push(
+                    Diagnostic::error(
+                        format!("task \"{id}\" has generated tests, but no reference"),
+                        locate(&["tasks", id, "generated"]),
+                    )
+                    .suggest("set command printing expected output, e.g. reference = \"python3 brute.py\""),
+                );
+            }
+            if task.tests.is_empty() && task.generated.is_empty() {
+                diagnostics.push(Diagnostic::warning(
+                    format!("task \"{id}\" has no tests"),
+                    location,
+                ));
+            }
+        }
+
+        for (id, contest) in &self.contests {
+            let Some(dir) = &contest.dir else {
+                continue;
+            };
+            let full = self.dir.as_ref().map_or(dir.clone(), |d| d.join(dir));
+            if !full.join(CONFIG_FILE_NAME).is_file() {
+                diagnostics.push(
+                    Diagnostic::warning(
+                        format!(
+                            "{CONFIG_FILE_NAME} of contest \"{id}\" not found in {}",
+                            dir.display()
+                        ),
+                        locate(&["contests", id, "dir"]),
+                    )
+                    .suggest(format!("run `cdf init` in {}", dir.display())),
+                );
+            }
+        }
+
+        for (lang, path) in &self.templates {
+            let full = self.dir.as_ref().map_or(path.clone(), |d| d.join(path));
+            if !full.is_file() {
+                diagnostics.push(
+                    Diagnostic::warning(
+                        format!("template {} does not exist", path.display()),
+                        locate(&["templates", lang]),
+                    )
+                    .suggest("create it or fix path"),
+                );
+            }
+        }
+        diagnostics
+    }
+    fn validate_build_settings(
+        &self,
+        path: &[&str],
+        settings: &BuildSettings,
+        locate: &impl Fn(&[&str]) -> Option<Location>,
+        diagnostics: &mut Vec<Diagnostic>,
+    ) {
+        let name = path.join(".");
+        let key = |k: &'static str| [path, &[k]].concat();
+        if settings.run.trim().is_empty() {
+            diagnostics.push(
+                Diagnostic::error(
+                    format!("{name}.run is empty"),
+                    locate(&key("run")).or(locate(path)),
+                )
+                .suggest("set command to run program, e.g. run = \"./{id}.out\""),
+            );
+        }
+        if settings.build.as_ref().is_some_and(|b| b.trim().is_empty()) {
+            diagnostics.push(
+                Diagnostic::error(format!("{name}.build is empty"), locate(&key("build")))
+                    .suggest("remove it if program does not need building"),
+            );
+        }
+        // key of command, command and placeholders available in it
+        let mut commands: Vec<(Vec<&str>, &String, &[&str])> =
+            vec![(key("run"), &settings.run, PLACEHOLDERS)];
+        commands.extend(
+            settings
+                .build
+                .iter()
+                .map(|b| (key("build"), b, PLACEHOLDERS)),
+        );
+        commands.extend(
+            settings
+                .source
+                .iter()
+                .map(|s| (key("source"), s, PLACEHOLDERS)),
+        );
+        commands.extend(
+            settings
+                .debugger
+                .iter()
+                .map(|d| (key("debugger"), d, DEBUGGER_PLACEHOLDERS)),
+        );
+        for (name, profile) in &settings.profiles {
+            let profile_key = |k| [path, &["profiles", name.as_str(), k]].concat();
+            commands.extend(
+                profile
+                    .build
+                    .iter()
+                    .map(|b| (profile_key("build"), b, PLACEHOLDERS)),
+            );
+            commands.extend(
+                profile
+                    .run
+                    .iter()
+                    .map(|r| (profile_key("run"), r, PLACEHOLDERS)),
+            );
+        }
+        for (k, cmd, allowed) in commands {
+            if let Some(quote) = unclosed_quote(cmd) {
+                diagnostics.push(quote_warning(quote, &k.join("."), locate(&k)));
+            }
+            for p in placeholders(cmd).filter(|p| !allowed.contains(p)) {
+                let available: Vec<String> = allowed.iter().map(|p| format!("{{{p}}}")).collect();
+                diagnostics.push(
+                    Diagnostic::error(
+                        format!("unknown placeholder {{{p}}} in {}", k.join(".")),
+                        locate(&k),
+                    )
+                    .suggest(format!("available placeholders: {}", available.join(", "))),
+                );
+            }
+        }
+        if let Some(cwd) = &settings.cwd {
+            let full = self.dir.as_ref().map_or(cwd.clone(), |d| d.join(cwd));
+            if !full.is_dir() {
+                diagnostics.push(
+                    Diagnostic::error(
+                        format!("working directory {} does not exist", cwd.display()),
+                        locate(&key("cwd")),
+                    )
+                    .suggest("create it or fix path"),
+                );
+            }
+        }
+    }
+}
+
+/// Warning about unclosed quote, which makes rest of command one word
+fn quote_warning(quote: char, name: &str, location: Option<Location>) -> Diagnostic {
+    Diagnostic::warning(format!("unclosed quote {quote} in {name}"), location).suggest(
+        "words in quotes are passed as one argument, close the quote or use the other kind of quotes",
+    )
+}
+
+/// Names of `{placeholders}` in command. Only identifier-like names are considered, so
+/// that braces of shell and awk in commands are not reported
+fn placeholders(cmd: &str) -> impl Iterator<Item = &str> {
+    cmd.split('{')
+        .skip(1)
+        .filter_map(|s| s.split_once('}').map(|(p, _)| p))
+        .filter(|p| !p.is_empty() && p.bytes().all(|b| b.is_ascii_lowercase() || b == b'_'))
+}
+
+/// Span of value by path of keys
+fn span(item: &Item, path: &[&str]) -> Option<Range<usize>> {
+    let Some((key, rest)) = path.split_first() else {
+        return item.span();
+    };
+    let table = item.as_table_like()?;
+    let next = table.get(key)?;
+    span(next, rest).or_else(|| {
+        // implicit tables have no span, use span of key
+        rest.is_empty()
+            .then(|| table.get_key_value(key).and_then(|(k, _)| k.span()))
+            .flatten()
+    })
+}
+
+fn location(source: &str, offset: usize) -> Location {
+    let before = &source[..offset.min(source.len())];
+    let line = before.matches('\n').count() + 1;
+    let column = before.rsplit('\n').next().map_or(0, |l| l.chars().count()) + 1;
+    Location { line, column }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"
+[settings.build]
+run = "./{id}.out"
+build = "sh -c 'g++ {id}.cpp"
+
+[tasks.a]
+name = "A"
+generator = "python3 gen.py {seed} {input}"
+validator = "sh -c 'f() { :; }; f' && awk '{print $1}'"
+tests = [{ input = "1", expected = "1" }]
+
+[tasks.B]
+name = "B"
+tests = []
+"#;
+
+    fn diagnostics() -> Vec<Diagnostic> {
+        Config::try_from(CONFIG).unwrap().validate()
+    }
+
+    #[test]
+    fn ignores_braces_of_shell_and_awk() {
+        assert_eq!(
+            placeholders("sh -c 'f() { :; }' && awk '{print $1}' {id} {}").collect::<Vec<_>>(),
+            ["id"]
+        );
+    }
+
+    #[test]
+    fn reports_unknown_placeholder_with_location() {
+        let diagnostics = diagnostics();
+        let unknown: Vec<_> = diagnostics
+            .iter()
+            .filter(|d| d.message.contains("placeholder"))
+            .collect();
+        assert_eq!(unknown.len(), 1);
+        let d = unknown[0];
+        assert_eq!(
+            d.message,
+            "unknown placeholder {input} in tasks.a.generator"
+        );
+        assert_eq!(d.severity, Severity::Error);
+        assert_eq!(
+            d.location,
+            Some(Location {
+                line: 8,
+                column: 13
+            })
+        );
+        assert_eq!(
+            d.suggestion.as_deref(),
+            Some("available placeholders: {id}, {seed}")
+        );
+    }
+
+    #[test]
+    fn warns_about_task_problems() {
+        let diagnostics = diagnostics();
+        let warnings: Vec<_> = diagnostics
+            .iter()
+            .filter(|d| d.severity == Severity::Warning)
+            .map(|d| d.message.as_str())
+            .collect();
+        assert_eq!(
+            warnings,
+            [
+                "unclosed quote ' in settings.build.build",
+                "task id \"B\" contains uppercase letters",
+                "task \"B\" has no tests"
+            ]
+        );
+        let uppercase = diagnostics.iter().find(|d| d.message.contains("uppercase"));
+        assert_eq!(
+            uppercase.unwrap().to_string(),
+            "12:1: warning: task id \"B\" contains uppercase letters\n  \
+             help: ids are lowercased when adding tasks, rename it to \"b\""
+        );
+    }
+
+    #[test]
+    fn warns_about_unclosed_quote() {
+        let diagnostics = diagnostics();
+        let d = diagnostics
+            .iter()
+            .find(|d| d.message.contains("quote"))
+            .unwrap();
+        assert_eq!(d.severity, Severity::Warning);
+        assert_eq!(d.location, Some(Location { line: 4, column: 9 }));
+        assert!(d.suggestion.is_some());
+    }
+}
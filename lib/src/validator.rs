@@ -0,0 +1,150 @@
+//! Checking of test inputs with validator of task, e.g. written with testlib
+
+use crate::{exec::exec, Config, Error, Result, TaskID};
+
+/// Test with input rejected by validator
+#[derive(Debug, Clone)]
+pub struct InvalidTest {
+    /// Index in tests followed by generated tests
+    pub index: usize,
+    /// Message of validator
+    pub message: String,
+    pub generated: bool,
+}
+
+impl Config {
+    /// Check input with validator of task, which exits with error for invalid input.
+    /// Input is valid if task has no validator
+    pub fn check_input(&self, id: &TaskID, input: &str) -> Result<()> {
+        let Some(validator) = self.tasks.get(id).and_then(|t| t.validator.as_ref()) else {
+            return Ok(());
+        };
+        let cwd = self
+            .build_settings(id)
+            .ok()
+            .and_then(|s| self.prepare_from_dir(s, &self.dir));
+        let output = exec(
+            validator.replace("{id}", id),
+            Some(input.into()),
+            cwd,
+            self.cancel_token(),
+            None,
+        )?;
+        if output.success {
+            return Ok(());
+        }
+        // testlib prints message to stderr
+        let message = [&output.stderr, &output.stdout]
+            .into_iter()
+            .map(|s| s.trim())
+            .find(|s| !s.is_empty())
+            .unwrap_or("validator failed without message");
+        Err(Error::InvalidInput(message.into()))
+    }
+    /// Input is accepted by validator, errors of running validator are returned
+    pub(crate) fn is_valid_input(&self, id: &TaskID, input: &str) -> Result<bool> {
+        match self.check_input(id, input) {
+            Ok(()) => Ok(true),
+            Err(Error::InvalidInput(_)) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+    /// Check input printed by generator with `seed`
+    pub(crate) fn check_generated_input(&self, id: &TaskID, seed: u64, input: &str) -> Result<()> {
+        self.check_input(id, input).map_err(|e| match e {
+            Error::InvalidInput(message) => Error::InvalidGeneratedInput(seed, message),
+            e => e,
+        })
+    }
+    /// Check inputs of all tests of task, including generated ones, returning rejected ones
+    pub fn check_tests(&self, id: &TaskID) -> Result<Vec<InvalidTest>> {
+        let task = self
+            .tasks
+            .get(id)
+            .ok_or_else(|| Error::TaskNotFound(id.clone()))?;
+        if task.validator.is_none() {
+            return Err(Error::ValidatorNotSet(id.clone()));
+        }
+        let literal = task.tests.iter().map(|t| Ok(t.input.clone()));
+        let generated = task.generated.iter().map(|t| self.generated_input(id, t));
+        let mut invalid = vec![];
+        for (index, input) in literal.chain(generated).enumerate() {
+            match self.check_input(id, &input?) {
+                Ok(()) => (),
+                Err(Error::InvalidInput(message)) => invalid.push(InvalidTest {
+                    index,
+                    message,
+                    generated: index >= task.tests.len(),
+                }),
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(invalid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::write;
+
+    use super::*;
+    use crate::temp::TempDir;
+
+    const CONFIG: &str = r#"
+[settings.build]
+run = "sh {id}.sh"
+
+[tasks.a]
+name = "Sum"
+validator = "sh valid.sh"
+reference = "sh a.sh"
+generated = [
+    { generator = "sh gen.sh {seed}", seed = 1 },
+    { generator = "sh gen.sh {seed}", seed = 2 },
+]
+
+[[tasks.a.tests]]
+input = "1 2"
+expected = "3"
+
+[[tasks.a.tests]]
+input = "500 2"
+expected = "502"
+"#;
+
+    #[test]
+    fn check_tests_includes_generated() {
+        let dir = TempDir::new("test").unwrap();
+        write(dir.path().join("gen.sh"), "echo $(($1 * 60)) 1").unwrap();
+        write(
+            dir.path().join("valid.sh"),
+            "read a b; test $a -le 100 || { echo a is too large >&2; exit 1; }",
+        )
+        .unwrap();
+        let mut config = Config::try_from(CONFIG).unwrap();
+        config.dir = Some(dir.path().into());
+
+        let id = "a".into();
+        assert!(config.is_valid_input(&id, "1 2").unwrap());
+        let invalid = config.check_tests(&id).unwrap();
+        let invalid: Vec<_> = invalid
+            .iter()
+            .map(|t| (t.index, t.message.as_str(), t.generated))
+            .collect();
+        assert_eq!(
+            invalid,
+            [(1, "a is too large", false), (3, "a is too large", true)]
+        );
+    }
+
+    #[test]
+    fn check_tests_requires_validator() {
+        let mut config = Config::default();
+        config.add_task(&"a".into(), "Echo");
+        assert!(matches!(
+            config.check_tests(&"a".into()),
+            Err(Error::ValidatorNotSet(_))
+        ));
+        assert!(config.check_input(&"a".into(), "anything").is_ok());
+    }
+}
@@ -0,0 +1,195 @@
+use std::{fmt::Display, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{crash::parse_crash, CommandOutput, FailedTest, TestResult};
+
+/// Verdict of local test or of submission
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Verdict {
+    Accepted,
+    WrongAnswer,
+    RuntimeError,
+    TimeLimitExceeded,
+    MemoryLimitExceeded,
+    CompilationError,
+    /// Testing failed because of tester or judge
+    Failed,
+    /// Verdict without equivalent here, as reported by judge
+    Other(String),
+}
+
+impl Verdict {
+    /// Parse verdict in format of Codeforces API, e.g. `WRONG_ANSWER`
+    pub fn from_codeforces(s: &str) -> Self {
+        match s {
+            "OK" => Self::Accepted,
+            "WRONG_ANSWER" => Self::WrongAnswer,
+            "RUNTIME_ERROR" => Self::RuntimeError,
+            "TIME_LIMIT_EXCEEDED" | "IDLENESS_LIMIT_EXCEEDED" => Self::TimeLimitExceeded,
+            "MEMORY_LIMIT_EXCEEDED" => Self::MemoryLimitExceeded,
+            "COMPILATION_ERROR" => Self::CompilationError,
+            "FAILED" => Self::Failed,
+            s => Self::Other(s.into()),
+        }
+    }
+    pub fn is_accepted(&self) -> bool {
+        *self == Self::Accepted
+    }
+}
+
+impl Display for Verdict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Accepted => write!(f, "accepted"),
+            Self::WrongAnswer => write!(f, "wrong answer"),
+            Self::RuntimeError => write!(f, "runtime error"),
+            Self::TimeLimitExceeded => write!(f, "time limit exceeded"),
+            Self::MemoryLimitExceeded => write!(f, "memory limit exceeded"),
+            Self::CompilationError => write!(f, "compilation error"),
+            Self::Failed => write!(f, "testing failed"),
+            Self::Other(s) => write!(f, "{}", s.to_lowercase().replace('_', " ")),
+        }
+    }
+}
+
+impl FailedTest {
+    pub fn verdict(&self) -> Verdict {
+        if self.is_runtime_error() {
+            Verdict::RuntimeError
+        } else {
+            Verdict::WrongAnswer
+        }
+    }
+}
+
+impl CommandOutput {
+    /// Program exited with error or reported a crash in stderr
+    pub fn is_runtime_error(&self) -> bool {
+        !self.success || parse_crash(&self.stderr).is_some()
+    }
+    /// Output matches `expected`. If `strict`, program must also exit successfully without
+    /// crash report
+    pub(crate) fn is_accepted(&self, expected: &str, strict: bool) -> bool {
+        self.stdout.trim() == expected.trim() && !(strict && self.is_runtime_error())
+    }
+}
+
+impl TestResult {
+    pub fn verdict(&self) -> Verdict {
+        match self {
+            Self::Ok(_) => Verdict::Accepted,
+            Self::Failed(f) => f.verdict(),
+            Self::Err(_) | Self::Cancelled => Verdict::Failed,
+        }
+    }
+    /// Time of running program
+    pub fn time(&self) -> Option<Duration> {
+        match self {
+            Self::Ok(time) => Some(*time),
+            Self::Failed(f) => Some(f.cmd_output.time),
+            Self::Err(_) | Self::Cancelled => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::write;
+
+    use super::*;
+    use crate::{temp::TempDir, Config};
+
+    const LEAK: &str = "\
+=================================================================
+==42==ERROR: LeakSanitizer: detected memory leaks
+
+Direct leak of 4 byte(s) in 1 object(s) allocated from:
+    #0 0x7f3a in operator new(unsigned long) (/lib/libasan.so.8+0xfe)
+    #1 0x55d1 in main /src/a.cpp:4:14
+
+SUMMARY: AddressSanitizer: 4 byte(s) leaked in 1 allocation(s).
+";
+
+    fn output(stdout: &str, stderr: &str, success: bool) -> CommandOutput {
+        CommandOutput {
+            stdout: stdout.into(),
+            stderr: stderr.into(),
+            success,
+            time: Duration::ZERO,
+        }
+    }
+
+    #[test]
+    fn accepts_only_successful_output_without_crash_if_strict() {
+        assert!(output("3\n", "debug output", true).is_accepted("3", true));
+        assert!(!output("4\n", "", true).is_accepted("3", true));
+        assert!(!output("3\n", "", false).is_accepted("3", true));
+        assert!(!output("3\n", LEAK, false).is_accepted("3", true));
+        // UndefinedBehaviorSanitizer does not exit with error by default
+        let ubsan = "a.cpp:5:7: runtime error: signed integer overflow";
+        assert!(!output("3\n", ubsan, true).is_accepted("3", true));
+    }
+
+    #[test]
+    fn accepts_matching_output_if_not_strict() {
+        assert!(output("3\n", "", false).is_accepted("3", false));
+        assert!(output("3\n", LEAK, false).is_accepted("3", false));
+        assert!(!output("4\n", "", true).is_accepted("3", false));
+    }
+
+    #[test]
+    fn detects_runtime_error() {
+        assert!(!output("3", "debug output", true).is_runtime_error());
+        assert!(output("3", "", false).is_runtime_error());
+        assert!(output("3", LEAK, true).is_runtime_error());
+    }
+
+    #[test]
+    fn verdict_of_failed_test() {
+        let failed = |output| FailedTest::new(0, "1 2", "3", output);
+        assert_eq!(
+            failed(output("4", "", true)).verdict(),
+            Verdict::WrongAnswer
+        );
+        assert_eq!(
+            failed(output("", "", false)).verdict(),
+            Verdict::RuntimeError
+        );
+        let leak = failed(output("3", LEAK, false));
+        assert_eq!(leak.verdict(), Verdict::RuntimeError);
+        assert!(leak.crash.is_some());
+    }
+
+    #[test]
+    fn leak_with_correct_output_fails_test_only_if_enabled() {
+        let dir = TempDir::new("test").unwrap();
+        write(dir.path().join("leak.txt"), LEAK).unwrap();
+        write(dir.path().join("a.sh"), "echo 3; cat leak.txt >&2; exit 23").unwrap();
+        for (strict, verdict) in [(false, Verdict::Accepted), (true, Verdict::RuntimeError)] {
+            let mut config = Config::try_from(
+                format!(
+                    r#"
+[settings.build]
+run = "sh {{id}}.sh"
+fail_on_runtime_error = {strict}
+
+[tasks.a]
+name = "Sum"
+
+[[tasks.a.tests]]
+input = "1 2"
+expected = "3"
+"#
+                )
+                .as_str(),
+            )
+            .unwrap();
+            config.dir = Some(dir.path().into());
+            let results: Vec<_> = config.run_tests(&"a".into()).into_iter().collect();
+            assert_eq!(results.len(), 1);
+            assert_eq!(results[0].verdict(), verdict);
+        }
+    }
+}